@@ -0,0 +1,269 @@
+//! Decision Attestation
+//!
+//! Signs each emitted optimization decision (the chosen route plus its
+//! constraint evaluation) so it is tamper-evident and auditable after the
+//! fact. Mirrors `constraint_source`/`graph_source`: a `Signer` trait
+//! decouples "how a decision gets signed" from the optimizer, with a local
+//! Ed25519 keypair implementation and a pluggable remote (e.g. KMS) signer,
+//! so the private key never has to live in this process when the latter is
+//! configured.
+
+use crate::optimizer::CandidateRoute;
+use crate::Config;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tonic::async_trait;
+
+/// A signature over a decision's canonical form, plus enough metadata for
+/// an auditor to verify it independently of this process later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attestation {
+    pub key_id: String,
+    /// Hex-encoded Ed25519 signature bytes.
+    pub signature: String,
+    pub signed_at: DateTime<Utc>,
+}
+
+/// Signs an arbitrary payload (the canonical bytes of a decision). Reports
+/// a `key_id` so a verifier knows which public key to check the signature
+/// against; the private key material itself is never exposed through this
+/// trait, so a `RemoteSigner` can keep it entirely outside this process.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    async fn sign(&self, payload: &[u8]) -> Result<Vec<u8>>;
+    fn key_id(&self) -> &str;
+}
+
+/// Signs locally with an Ed25519 keypair held in process memory. Simplest
+/// to operate, but the private key lives wherever this process runs.
+pub struct Ed25519Signer {
+    signing_key: SigningKey,
+    key_id: String,
+}
+
+impl Ed25519Signer {
+    pub fn from_seed(seed: &[u8; 32], key_id: impl Into<String>) -> Self {
+        Ed25519Signer {
+            signing_key: SigningKey::from_bytes(seed),
+            key_id: key_id.into(),
+        }
+    }
+
+    pub fn verifying_key(&self) -> VerifyingKey {
+        self.signing_key.verifying_key()
+    }
+}
+
+#[async_trait]
+impl Signer for Ed25519Signer {
+    async fn sign(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        use ed25519_dalek::Signer as _;
+        Ok(self.signing_key.sign(payload).to_bytes().to_vec())
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+/// Signs via an external KMS (or any HTTP signing endpoint), so the private
+/// key never needs to live in this process. Expects the endpoint to accept
+/// `{"key_id": "...", "payload": "<hex>"}` and return `{"signature": "<hex>"}`.
+pub struct RemoteSigner {
+    client: reqwest::Client,
+    endpoint: String,
+    key_id: String,
+}
+
+impl RemoteSigner {
+    pub fn new(endpoint: impl Into<String>, key_id: impl Into<String>) -> Self {
+        RemoteSigner {
+            client: reqwest::Client::new(),
+            endpoint: endpoint.into(),
+            key_id: key_id.into(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RemoteSignRequest<'a> {
+    key_id: &'a str,
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct RemoteSignResponse {
+    signature: String,
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn sign(&self, payload: &[u8]) -> Result<Vec<u8>> {
+        let body = RemoteSignRequest {
+            key_id: &self.key_id,
+            payload: hex::encode(payload),
+        };
+        let resp: RemoteSignResponse = self
+            .client
+            .post(&self.endpoint)
+            .json(&body)
+            .send()
+            .await
+            .context("Remote signer request failed")?
+            .json()
+            .await
+            .context("Remote signer returned an unparsable response")?;
+        hex::decode(resp.signature.trim()).context("Remote signer returned invalid hex")
+    }
+
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+}
+
+/// Builds the configured `Signer` from `Config`, or `None` if attestation is
+/// disabled or misconfigured (in which case decisions are emitted unsigned,
+/// same as before this feature existed).
+pub fn default_signer(config: &Config) -> Option<Arc<dyn Signer>> {
+    if !config.attestation_enabled {
+        return None;
+    }
+    if let Some(ref endpoint) = config.attestation_remote_signer_url {
+        return Some(Arc::new(RemoteSigner::new(
+            endpoint.clone(),
+            config.attestation_key_id.clone(),
+        )));
+    }
+    let seed = config.attestation_local_seed.as_ref().and_then(|hex_seed| {
+        let bytes = hex::decode(hex_seed.trim()).ok()?;
+        let array: [u8; 32] = bytes.try_into().ok()?;
+        Some(array)
+    });
+    match seed {
+        Some(seed) => Some(Arc::new(Ed25519Signer::from_seed(
+            &seed,
+            config.attestation_key_id.clone(),
+        ))),
+        None => {
+            tracing::warn!(
+                "ATTESTATION_ENABLED is set but no usable signer is configured \
+                 (ATTESTATION_REMOTE_SIGNER_URL or ATTESTATION_LOCAL_SEED); decisions will not be signed"
+            );
+            None
+        }
+    }
+}
+
+/// The fields of a decision that get signed, in canonical (deterministic)
+/// form: the chosen route's identity, segment carrier codes in route order,
+/// its totals, its constraint pass flags, and the time it was produced.
+/// Derived/advisory fields (weighted_score, pareto_rank) are intentionally
+/// left out of the attested payload.
+fn canonical_payload(route: &CandidateRoute, signed_at: DateTime<Utc>) -> Vec<u8> {
+    let carrier_codes: Vec<&str> = route
+        .segments
+        .iter()
+        .map(|s| s.carrier_code.as_str())
+        .collect();
+
+    let mut constraint_flags: Vec<String> = route
+        .constraint_results
+        .iter()
+        .map(|r| format!("{}:{}", r.constraint_id, r.passed))
+        .collect();
+    constraint_flags.sort_unstable();
+
+    format!(
+        "route_id={}|carriers={}|cost_usd={}|carbon_kg={}|time_hours={}|constraints={}|signed_at={}",
+        route.route_id,
+        carrier_codes.join(","),
+        route.total_cost_usd,
+        route.total_carbon_kg,
+        route.total_time_hours,
+        constraint_flags.join(","),
+        signed_at.to_rfc3339(),
+    )
+    .into_bytes()
+}
+
+/// Sign a decision (the chosen route and its constraint evaluation) with
+/// the given `Signer`, returning the attestation to attach to the response.
+pub async fn sign_decision(signer: &dyn Signer, route: &CandidateRoute) -> Result<Attestation> {
+    let signed_at = Utc::now();
+    let payload = canonical_payload(route, signed_at);
+    let signature = signer.sign(&payload).await?;
+    Ok(Attestation {
+        key_id: signer.key_id().to_string(),
+        signature: hex::encode(signature),
+        signed_at,
+    })
+}
+
+/// Verify that `attestation` was produced, by the holder of `verifying_key`,
+/// over exactly this `route` — used by auditors who only have the public
+/// key, not the live system.
+pub fn verify_decision(
+    verifying_key: &VerifyingKey,
+    route: &CandidateRoute,
+    attestation: &Attestation,
+) -> Result<bool> {
+    use ed25519_dalek::Verifier as _;
+
+    let payload = canonical_payload(route, attestation.signed_at);
+    let sig_bytes =
+        hex::decode(attestation.signature.trim()).context("Attestation signature is not valid hex")?;
+    let signature =
+        Signature::from_slice(&sig_bytes).context("Attestation signature is malformed")?;
+    Ok(verifying_key.verify(&payload, &signature).is_ok())
+}
+
+/// A previously emitted decision, as an auditor would have it on disk: the
+/// route it decided on and the attestation that was attached to it.
+#[derive(Debug, Deserialize)]
+struct StoredDecision {
+    route: CandidateRoute,
+    attestation: Attestation,
+}
+
+/// `verify <record.json> [--public-key <hex>]` — the binary's auditor-facing
+/// mode. Loads a stored decision record and confirms its attestation was
+/// produced by the holder of the given public key and matches the stored
+/// route exactly (i.e. the record has not been altered since it was signed).
+pub async fn run_verify(args: &[String]) -> Result<()> {
+    let record_path = args
+        .first()
+        .context("usage: verify <record.json> [--public-key <hex>]")?;
+
+    let public_key_hex = args
+        .iter()
+        .position(|a| a == "--public-key")
+        .and_then(|i| args.get(i + 1).cloned())
+        .or_else(|| std::env::var("ATTESTATION_PUBLIC_KEY").ok())
+        .context("no --public-key given and ATTESTATION_PUBLIC_KEY is not set")?;
+
+    let data = tokio::fs::read_to_string(record_path)
+        .await
+        .with_context(|| format!("Failed to read decision record at {}", record_path))?;
+    let stored: StoredDecision = serde_json::from_str(&data)
+        .context("Failed to parse stored decision record")?;
+
+    let key_bytes = hex::decode(public_key_hex.trim()).context("--public-key is not valid hex")?;
+    let key_array: [u8; 32] = key_bytes
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("public key must be exactly 32 bytes"))?;
+    let verifying_key =
+        VerifyingKey::from_bytes(&key_array).context("invalid Ed25519 public key")?;
+
+    if verify_decision(&verifying_key, &stored.route, &stored.attestation)? {
+        println!(
+            "OK: attestation verified (key_id={}, route_id={}, signed_at={})",
+            stored.attestation.key_id, stored.route.route_id, stored.attestation.signed_at
+        );
+        Ok(())
+    } else {
+        anyhow::bail!("FAILED: attestation signature does not match the stored decision");
+    }
+}
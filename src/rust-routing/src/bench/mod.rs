@@ -0,0 +1,341 @@
+//! Benchmark Harness
+//!
+//! Replays recorded `OptimizeRequest` workloads against the optimizer and
+//! constraint engine and reports latency/throughput, so maintainers can
+//! catch optimizer performance regressions before release. Invoked via the
+//! `bench` subcommand: `veds-route-optimizer bench <workload.json|dir> [--report-url <url>]`.
+
+use crate::constraints::ConstraintEngine;
+use crate::graph_source::{FileGraphSource, GraphSource};
+use crate::optimizer::{OptimizeRequest, Optimizer};
+use crate::Config;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+/// A workload file: a named scenario replayed against a graph fixture.
+#[derive(Debug, Deserialize)]
+struct WorkloadFile {
+    name: String,
+    graph_fixture: String,
+    requests: Vec<WorkloadRequest>,
+    #[serde(default = "default_repeat")]
+    repeat: usize,
+}
+
+fn default_repeat() -> usize {
+    1
+}
+
+/// JSON-friendly mirror of `optimizer::OptimizeRequest`.
+#[derive(Debug, Deserialize)]
+struct WorkloadRequest {
+    #[serde(default)]
+    shipment_id: String,
+    origin_code: String,
+    destination_code: String,
+    weight_kg: f64,
+    #[serde(default = "default_volume")]
+    volume_m3: f64,
+    #[serde(default)]
+    pickup_after: Option<String>,
+    #[serde(default)]
+    deliver_by: Option<String>,
+    #[serde(default)]
+    max_cost_usd: Option<f64>,
+    #[serde(default)]
+    max_carbon_kg: Option<f64>,
+    #[serde(default)]
+    min_labor_score: Option<f64>,
+    #[serde(default)]
+    allowed_modes: Vec<String>,
+    #[serde(default)]
+    excluded_carriers: Vec<String>,
+    #[serde(default = "default_max_routes")]
+    max_routes: usize,
+    #[serde(default = "default_max_segments")]
+    max_segments: usize,
+    #[serde(default = "default_cost_weight")]
+    cost_weight: f64,
+    #[serde(default = "default_time_weight")]
+    time_weight: f64,
+    #[serde(default = "default_carbon_weight")]
+    carbon_weight: f64,
+    #[serde(default = "default_labor_weight")]
+    labor_weight: f64,
+    #[serde(default)]
+    heuristic: WorkloadHeuristic,
+    #[serde(default = "default_greedy_factor")]
+    greedy_factor: f64,
+    #[serde(default)]
+    waypoints: Vec<String>,
+    #[serde(default)]
+    arrival_weight: f64,
+    #[serde(default)]
+    maximize_slack: bool,
+    #[serde(default)]
+    lns_iterations: usize,
+}
+
+/// JSON-friendly mirror of `optimizer::HeuristicKind`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum WorkloadHeuristic {
+    #[default]
+    Dijkstra,
+    Astar,
+}
+
+fn default_volume() -> f64 {
+    1.0
+}
+fn default_max_routes() -> usize {
+    10
+}
+fn default_max_segments() -> usize {
+    8
+}
+fn default_cost_weight() -> f64 {
+    0.4
+}
+fn default_time_weight() -> f64 {
+    0.3
+}
+fn default_carbon_weight() -> f64 {
+    0.2
+}
+fn default_labor_weight() -> f64 {
+    0.1
+}
+fn default_greedy_factor() -> f64 {
+    1.0
+}
+
+impl WorkloadRequest {
+    fn into_optimize_request(self) -> OptimizeRequest {
+        let pickup_after = self
+            .pickup_after
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(Utc::now);
+
+        let deliver_by = self
+            .deliver_by
+            .as_deref()
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|| pickup_after + Duration::days(30));
+
+        let allowed_modes = self
+            .allowed_modes
+            .iter()
+            .filter_map(|m| match m.to_uppercase().as_str() {
+                "MARITIME" => Some(crate::graph::TransportMode::Maritime),
+                "RAIL" => Some(crate::graph::TransportMode::Rail),
+                "ROAD" => Some(crate::graph::TransportMode::Road),
+                "AIR" => Some(crate::graph::TransportMode::Air),
+                _ => None,
+            })
+            .collect();
+
+        OptimizeRequest {
+            shipment_id: self.shipment_id,
+            origin_code: self.origin_code,
+            destination_code: self.destination_code,
+            weight_kg: self.weight_kg,
+            volume_m3: self.volume_m3,
+            pickup_after,
+            deliver_by,
+            max_cost_usd: self.max_cost_usd.map(|v| Decimal::from_f64_retain(v).unwrap_or(Decimal::MAX)),
+            max_carbon_kg: self.max_carbon_kg,
+            min_labor_score: self.min_labor_score,
+            allowed_modes,
+            excluded_carriers: self.excluded_carriers.into_iter().collect::<HashSet<_>>(),
+            max_routes: self.max_routes,
+            max_segments: self.max_segments,
+            cost_weight: self.cost_weight,
+            time_weight: self.time_weight,
+            carbon_weight: self.carbon_weight,
+            labor_weight: self.labor_weight,
+            heuristic: match self.heuristic {
+                WorkloadHeuristic::Dijkstra => crate::optimizer::HeuristicKind::Dijkstra,
+                WorkloadHeuristic::Astar => crate::optimizer::HeuristicKind::AStar,
+            },
+            greedy_factor: self.greedy_factor,
+            waypoints: self.waypoints,
+            arrival_weight: self.arrival_weight,
+            maximize_slack: self.maximize_slack,
+            lns_iterations: self.lns_iterations,
+        }
+    }
+}
+
+/// Latency and outcome stats for one workload run.
+#[derive(Debug, Serialize)]
+pub struct WorkloadReport {
+    pub workload: String,
+    pub total_runs: usize,
+    pub min_latency_ms: f64,
+    pub mean_latency_ms: f64,
+    pub p50_latency_ms: f64,
+    pub p90_latency_ms: f64,
+    pub p99_latency_ms: f64,
+    pub throughput_rps: f64,
+    pub hard_constraint_pass_rate: f64,
+    pub avg_segments_per_route: f64,
+    pub avg_candidates_evaluated: f64,
+}
+
+fn percentile(sorted_latencies_ms: &[f64], p: f64) -> f64 {
+    if sorted_latencies_ms.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p * sorted_latencies_ms.len() as f64).ceil() as usize).max(1);
+    sorted_latencies_ms[rank.min(sorted_latencies_ms.len()) - 1]
+}
+
+/// Run a single workload file and produce its report.
+async fn run_workload(path: &Path, config: &Config) -> Result<WorkloadReport> {
+    let data = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read workload file {:?}", path))?;
+    let workload: WorkloadFile = serde_json::from_str(&data)
+        .with_context(|| format!("Failed to parse workload file {:?}", path))?;
+
+    let fixture_source = FileGraphSource::new(PathBuf::from(&workload.graph_fixture));
+    let graph = fixture_source
+        .load(config)
+        .await
+        .with_context(|| format!("Failed to load graph fixture {}", workload.graph_fixture))?;
+
+    let optimizer = Optimizer::new(ConstraintEngine::new());
+
+    let mut latencies_ms = Vec::with_capacity(workload.requests.len() * workload.repeat.max(1));
+    let mut requests_with_compliant_route = 0usize;
+    let mut requests_run = 0usize;
+    let mut total_segments = 0usize;
+    let mut total_routes = 0usize;
+    let mut total_candidates_evaluated = 0usize;
+
+    let wall_start = Instant::now();
+    for request in workload.requests {
+        let internal_request = request.into_optimize_request();
+        for _ in 0..workload.repeat.max(1) {
+            let start = Instant::now();
+            let result = optimizer.optimize(&graph, &internal_request);
+            latencies_ms.push(start.elapsed().as_secs_f64() * 1000.0);
+
+            // `optimize` already drops routes that fail a hard constraint, so a
+            // non-empty result means at least one compliant route was found.
+            requests_run += 1;
+            if !result.routes.is_empty() {
+                requests_with_compliant_route += 1;
+            }
+            total_segments += result.routes.iter().map(|r| r.segments.len()).sum::<usize>();
+            total_routes += result.routes.len();
+            total_candidates_evaluated += result.candidates_evaluated;
+        }
+    }
+    let wall_elapsed = wall_start.elapsed().as_secs_f64();
+
+    latencies_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let total_runs = latencies_ms.len();
+    let mean = if total_runs > 0 {
+        latencies_ms.iter().sum::<f64>() / total_runs as f64
+    } else {
+        0.0
+    };
+
+    Ok(WorkloadReport {
+        workload: workload.name,
+        total_runs,
+        min_latency_ms: latencies_ms.first().copied().unwrap_or(0.0),
+        mean_latency_ms: mean,
+        p50_latency_ms: percentile(&latencies_ms, 0.50),
+        p90_latency_ms: percentile(&latencies_ms, 0.90),
+        p99_latency_ms: percentile(&latencies_ms, 0.99),
+        throughput_rps: if wall_elapsed > 0.0 {
+            total_runs as f64 / wall_elapsed
+        } else {
+            0.0
+        },
+        hard_constraint_pass_rate: if requests_run > 0 {
+            requests_with_compliant_route as f64 / requests_run as f64
+        } else {
+            1.0
+        },
+        avg_segments_per_route: if total_routes > 0 {
+            total_segments as f64 / total_routes as f64
+        } else {
+            0.0
+        },
+        avg_candidates_evaluated: if requests_run > 0 {
+            total_candidates_evaluated as f64 / requests_run as f64
+        } else {
+            0.0
+        },
+    })
+}
+
+/// Entry point for the `bench` subcommand. `args` is everything after
+/// `bench` on the command line: a workload file or directory, and an
+/// optional `--report-url <url>`.
+pub async fn run(args: &[String]) -> Result<()> {
+    let config = Config::from_env()?;
+
+    let mut target: Option<PathBuf> = None;
+    let mut report_url: Option<String> = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == "--report-url" {
+            report_url = iter.next().cloned();
+        } else {
+            target = Some(PathBuf::from(arg));
+        }
+    }
+
+    let target = target.context("Usage: bench <workload.json|dir> [--report-url <url>]")?;
+
+    let mut workload_paths = Vec::new();
+    if target.is_dir() {
+        for entry in std::fs::read_dir(&target)? {
+            let entry = entry?;
+            if entry.path().extension().and_then(|e| e.to_str()) == Some("json") {
+                workload_paths.push(entry.path());
+            }
+        }
+        workload_paths.sort();
+    } else {
+        workload_paths.push(target);
+    }
+
+    let mut reports = Vec::with_capacity(workload_paths.len());
+    for path in &workload_paths {
+        let report = run_workload(path, &config).await?;
+        tracing::info!(
+            workload = %report.workload,
+            p50_ms = report.p50_latency_ms,
+            p99_ms = report.p99_latency_ms,
+            throughput_rps = report.throughput_rps,
+            avg_segments_per_route = report.avg_segments_per_route,
+            avg_candidates_evaluated = report.avg_candidates_evaluated,
+            "Workload complete"
+        );
+        reports.push(report);
+    }
+
+    let output = serde_json::to_string_pretty(&reports)?;
+
+    if let Some(url) = report_url {
+        let client = reqwest::Client::new();
+        client.post(&url).body(output.clone()).send().await?;
+    }
+
+    println!("{output}");
+    Ok(())
+}
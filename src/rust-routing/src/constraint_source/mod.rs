@@ -0,0 +1,164 @@
+//! Constraint Rule Source
+//!
+//! Decouples the constraint engine from a specific backing store, mirroring
+//! `graph_source`: the operator-maintained `ConstraintCache` (minimum wages,
+//! sanctioned carriers, and runtime-registered custom rules) can be built
+//! from Dragonfly, a local JSON snapshot, or an in-memory fixture, all
+//! behind the same `ConstraintRuleSource` trait.
+
+use crate::constraints::ConstraintCache;
+use crate::Config;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use tonic::async_trait;
+
+/// Builds a `ConstraintCache` from some backing source.
+#[async_trait]
+pub trait ConstraintRuleSource: Send + Sync {
+    async fn load_rules(&self) -> Result<ConstraintCache>;
+}
+
+/// Loads constraint rules from the live Dragonfly instance: minimum wages
+/// and sanctioned carriers as before, plus any custom rules registered
+/// under `constraint:custom:*`.
+pub struct DragonflyRuleSource {
+    redis: redis::Client,
+}
+
+impl DragonflyRuleSource {
+    pub fn new(redis: redis::Client) -> Self {
+        DragonflyRuleSource { redis }
+    }
+}
+
+#[async_trait]
+impl ConstraintRuleSource for DragonflyRuleSource {
+    async fn load_rules(&self) -> Result<ConstraintCache> {
+        let mut conn = redis::aio::ConnectionManager::new(self.redis.clone())
+            .await
+            .context("Failed to open Dragonfly connection for constraint rules")?;
+
+        let mut cache = crate::db::load_constraints_from_dragonfly(&mut conn).await?;
+        cache.custom_rules = crate::db::load_custom_rules_from_dragonfly(&mut conn).await?;
+        Ok(cache)
+    }
+}
+
+/// Loads the full constraint cache, including custom rules, from a local
+/// JSON snapshot — used for local development, CI, and as the last-good
+/// fallback if Dragonfly is unreachable.
+pub struct FileRuleSource {
+    path: PathBuf,
+}
+
+impl FileRuleSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileRuleSource { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl ConstraintRuleSource for FileRuleSource {
+    async fn load_rules(&self) -> Result<ConstraintCache> {
+        let data = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("Failed to read constraint rules snapshot at {:?}", self.path))?;
+
+        serde_json::from_str(&data)
+            .with_context(|| format!("Failed to parse constraint rules snapshot at {:?}", self.path))
+    }
+}
+
+/// Fixed in-memory constraint cache, for unit and integration tests that
+/// need a `ConstraintRuleSource` without touching the filesystem or network.
+pub struct FixtureRuleSource {
+    cache: ConstraintCache,
+}
+
+impl FixtureRuleSource {
+    pub fn new(cache: ConstraintCache) -> Self {
+        FixtureRuleSource { cache }
+    }
+}
+
+#[async_trait]
+impl ConstraintRuleSource for FixtureRuleSource {
+    async fn load_rules(&self) -> Result<ConstraintCache> {
+        Ok(self.cache.clone())
+    }
+}
+
+/// Wraps any two `ConstraintRuleSource`s so a Dragonfly outage degrades to
+/// the last-good on-disk snapshot instead of the constraint engine running
+/// with a stale in-memory cache (or failing outright) until an operator
+/// notices. The actual try/fall-back sequencing lives in `crate::fallback`,
+/// shared with `graph_source::FallbackGraphSource`.
+pub struct FallbackRuleSource<P: ConstraintRuleSource, F: ConstraintRuleSource> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: ConstraintRuleSource, F: ConstraintRuleSource> FallbackRuleSource<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        FallbackRuleSource { primary, fallback }
+    }
+}
+
+#[async_trait]
+impl<P: ConstraintRuleSource, F: ConstraintRuleSource> ConstraintRuleSource for FallbackRuleSource<P, F> {
+    async fn load_rules(&self) -> Result<ConstraintCache> {
+        crate::fallback::try_with_fallback(
+            "constraint rule source",
+            self.primary.load_rules(),
+            || self.fallback.load_rules(),
+        )
+        .await
+    }
+}
+
+/// Build the configured `ConstraintRuleSource` chain: Dragonfly with a
+/// fallback to the last-good on-disk snapshot if it is unreachable.
+pub fn default_rule_source(
+    config: &Config,
+    redis: redis::Client,
+) -> FallbackRuleSource<DragonflyRuleSource, FileRuleSource> {
+    FallbackRuleSource::new(
+        DragonflyRuleSource::new(redis),
+        FileRuleSource::new(Path::new(&config.constraint_rules_path)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fixture_source_returns_cache() {
+        let mut cache = ConstraintCache::default();
+        cache.sanctioned_carriers.insert("BADCO".to_string());
+        let source = FixtureRuleSource::new(cache);
+
+        let loaded = source.load_rules().await.unwrap();
+        assert!(loaded.sanctioned_carriers.contains("BADCO"));
+    }
+
+    #[tokio::test]
+    async fn test_fallback_source_uses_fallback_on_primary_error() {
+        struct AlwaysFails;
+
+        #[async_trait]
+        impl ConstraintRuleSource for AlwaysFails {
+            async fn load_rules(&self) -> Result<ConstraintCache> {
+                anyhow::bail!("primary unavailable")
+            }
+        }
+
+        let mut fallback_cache = ConstraintCache::default();
+        fallback_cache.min_wages.insert("US".to_string(), 1200);
+        let fallback = FixtureRuleSource::new(fallback_cache);
+        let source = FallbackRuleSource::new(AlwaysFails, fallback);
+
+        let loaded = source.load_rules().await.unwrap();
+        assert_eq!(loaded.min_wages.get("US"), Some(&1200));
+    }
+}
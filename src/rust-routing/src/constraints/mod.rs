@@ -1,7 +1,10 @@
 //! Constraint Engine
 //!
 //! Evaluates hard and soft constraints on routes.
-//! Uses cached constraint rules from Dragonfly for fast evaluation.
+//! Uses cached constraint rules from Dragonfly for fast evaluation, loaded
+//! through a pluggable `constraint_source::ConstraintRuleSource` so the
+//! cache (including runtime-registered custom rules) can come from
+//! Dragonfly, a local snapshot, or a test fixture interchangeably.
 
 use crate::optimizer::{CandidateRoute, OptimizeRequest, RouteSegment};
 use serde::{Deserialize, Serialize};
@@ -44,15 +47,87 @@ impl std::fmt::Display for ConstraintType {
     }
 }
 
+/// Aggregate view over a constraint evaluation, for dry-run "explain"
+/// callers that want a pass/fail summary without running optimization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteExplanation {
+    pub results: Vec<ConstraintResult>,
+    pub hard_constraints_passed: bool,
+    pub aggregate_soft_score: f64,
+}
+
 /// Cached constraint lookup tables (loaded from Dragonfly)
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConstraintCache {
     /// Minimum wage by country code (cents/hour)
+    #[serde(default)]
     pub min_wages: std::collections::HashMap<String, i32>,
     /// Maximum weekly hours by region
+    #[serde(default)]
     pub max_hours: std::collections::HashMap<String, i32>,
     /// Set of sanctioned carrier codes
+    #[serde(default)]
     pub sanctioned_carriers: std::collections::HashSet<String>,
+    /// Runtime-registered custom constraints (new sanctions lists,
+    /// region-specific caps, ...), backed by a `ConstraintRuleSource` and
+    /// applied without shipping a new binary.
+    #[serde(default)]
+    pub custom_rules: Vec<CustomConstraintRule>,
+}
+
+/// A declarative custom constraint rule: `field <op> value`, where `field`
+/// is a route aggregate (`total_cost_usd`, `total_carbon_kg`,
+/// `total_time_hours`, `total_distance_km`, `labor_score`) or a per-segment
+/// attribute (`segment.cost_usd`, `segment.carbon_kg`, `segment.distance_km`,
+/// `segment.transit_hours`, `segment.carrier_wage_cents`). A per-segment
+/// rule passes only if every segment in the route satisfies it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomConstraintRule {
+    pub constraint_id: String,
+    pub field: String,
+    pub op: ComparisonOp,
+    pub value: f64,
+    pub is_hard: bool,
+}
+
+/// Comparison operators supported in a custom constraint expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ComparisonOp {
+    #[serde(rename = "<=")]
+    Le,
+    #[serde(rename = ">=")]
+    Ge,
+    #[serde(rename = "<")]
+    Lt,
+    #[serde(rename = ">")]
+    Gt,
+    #[serde(rename = "==")]
+    Eq,
+}
+
+impl ComparisonOp {
+    fn apply(self, lhs: f64, rhs: f64) -> bool {
+        match self {
+            ComparisonOp::Le => lhs <= rhs,
+            ComparisonOp::Ge => lhs >= rhs,
+            ComparisonOp::Lt => lhs < rhs,
+            ComparisonOp::Gt => lhs > rhs,
+            ComparisonOp::Eq => (lhs - rhs).abs() < f64::EPSILON,
+        }
+    }
+}
+
+impl std::fmt::Display for ComparisonOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            ComparisonOp::Le => "<=",
+            ComparisonOp::Ge => ">=",
+            ComparisonOp::Lt => "<",
+            ComparisonOp::Gt => ">",
+            ComparisonOp::Eq => "==",
+        };
+        write!(f, "{symbol}")
+    }
 }
 
 /// Constraint engine
@@ -103,9 +178,90 @@ impl ConstraintEngine {
             results.push(self.check_labor_constraint(route, min_labor));
         }
 
+        // Runtime-registered custom constraints (operator-supplied, no
+        // binary rebuild required).
+        for rule in &self.cache.custom_rules {
+            results.push(self.check_custom_rule(route, rule));
+        }
+
         results
     }
 
+    /// Evaluate one runtime-registered custom rule against a route.
+    fn check_custom_rule(&self, route: &CandidateRoute, rule: &CustomConstraintRule) -> ConstraintResult {
+        let passed = if let Some(field) = rule.field.strip_prefix("segment.") {
+            route.segments.iter().all(|segment| {
+                Self::segment_field_value(segment, field)
+                    .map(|v| rule.op.apply(v, rule.value))
+                    .unwrap_or(true)
+            })
+        } else {
+            Self::route_field_value(route, &rule.field)
+                .map(|v| rule.op.apply(v, rule.value))
+                .unwrap_or(true)
+        };
+
+        ConstraintResult {
+            constraint_id: rule.constraint_id.clone(),
+            constraint_type: ConstraintType::Custom,
+            passed,
+            is_hard: rule.is_hard,
+            score: if passed { 1.0 } else { 0.0 },
+            message: format!("Custom rule '{}': {} {} {}", rule.constraint_id, rule.field, rule.op, rule.value),
+        }
+    }
+
+    /// Resolve a route-aggregate field by name, for custom rule evaluation.
+    fn route_field_value(route: &CandidateRoute, field: &str) -> Option<f64> {
+        match field {
+            "total_cost_usd" => route.total_cost_usd.to_string().parse().ok(),
+            "total_carbon_kg" => Some(route.total_carbon_kg),
+            "total_time_hours" => Some(route.total_time_hours),
+            "total_distance_km" => Some(route.total_distance_km),
+            "labor_score" => Some(route.labor_score),
+            _ => None,
+        }
+    }
+
+    /// Resolve a per-segment attribute by name, for custom rule evaluation.
+    fn segment_field_value(segment: &RouteSegment, field: &str) -> Option<f64> {
+        match field {
+            "cost_usd" => segment.cost_usd.to_string().parse().ok(),
+            "carbon_kg" => Some(segment.carbon_kg),
+            "transit_hours" => Some(segment.transit_hours),
+            "distance_km" => Some(segment.distance_km),
+            "carrier_wage_cents" => Some(segment.carrier_wage_cents as f64),
+            _ => None,
+        }
+    }
+
+    /// Dry-run evaluation for audit/explain callers: evaluates the route
+    /// exactly like `evaluate_route` (no cache mutation, no optimization),
+    /// then rolls the per-constraint results up into a pass/fail summary and
+    /// an aggregate soft-constraint score.
+    pub fn explain_route(
+        &self,
+        route: &CandidateRoute,
+        request: &OptimizeRequest,
+    ) -> RouteExplanation {
+        let results = self.evaluate_route(route, request);
+
+        let hard_constraints_passed = results.iter().filter(|r| r.is_hard).all(|r| r.passed);
+
+        let soft_results: Vec<&ConstraintResult> = results.iter().filter(|r| !r.is_hard).collect();
+        let aggregate_soft_score = if soft_results.is_empty() {
+            1.0
+        } else {
+            soft_results.iter().map(|r| r.score).sum::<f64>() / soft_results.len() as f64
+        };
+
+        RouteExplanation {
+            results,
+            hard_constraints_passed,
+            aggregate_soft_score,
+        }
+    }
+
     /// Check sanction constraint (HARD)
     fn check_sanction_constraint(&self, route: &CandidateRoute) -> ConstraintResult {
         let mut violations = Vec::new();
@@ -329,4 +485,61 @@ mod tests {
         let result = engine.check_sanction_constraint(&route);
         assert!(!result.passed);
     }
+
+    fn sample_route() -> CandidateRoute {
+        let mut route = CandidateRoute::new();
+        route.segments.push(RouteSegment {
+            segment_id: "s1".to_string(),
+            sequence: 0,
+            from_node: "A".to_string(),
+            to_node: "B".to_string(),
+            mode: crate::graph::TransportMode::Maritime,
+            carrier_code: "GOODCO".to_string(),
+            distance_km: 1000.0,
+            cost_usd: Decimal::from(100),
+            transit_hours: 24.0,
+            carbon_kg: 50.0,
+            carrier_wage_cents: 2000,
+            labor_score: 0.8,
+            departure_time: chrono::Utc::now(),
+            arrival_time: chrono::Utc::now(),
+        });
+        route.recalculate_totals();
+        route
+    }
+
+    #[test]
+    fn test_custom_rule_route_level() {
+        let mut cache = ConstraintCache::default();
+        cache.custom_rules.push(CustomConstraintRule {
+            constraint_id: "max-carbon-region".to_string(),
+            field: "total_carbon_kg".to_string(),
+            op: ComparisonOp::Le,
+            value: 40.0,
+            is_hard: false,
+        });
+        let engine = ConstraintEngine::with_cache(cache);
+
+        let route = sample_route();
+        let result = engine.check_custom_rule(&route, &engine.cache.custom_rules[0]);
+        assert!(!result.passed);
+        assert_eq!(result.constraint_type, ConstraintType::Custom);
+    }
+
+    #[test]
+    fn test_custom_rule_segment_level() {
+        let mut cache = ConstraintCache::default();
+        cache.custom_rules.push(CustomConstraintRule {
+            constraint_id: "min-segment-wage".to_string(),
+            field: "segment.carrier_wage_cents".to_string(),
+            op: ComparisonOp::Ge,
+            value: 1500.0,
+            is_hard: true,
+        });
+        let engine = ConstraintEngine::with_cache(cache);
+
+        let route = sample_route();
+        let result = engine.check_custom_rule(&route, &engine.cache.custom_rules[0]);
+        assert!(result.passed);
+    }
 }
@@ -10,8 +10,77 @@ use serde::Deserialize;
 use surrealdb::engine::remote::ws::{Client, Ws};
 use surrealdb::opt::auth::Root;
 use surrealdb::Surreal;
+use tokio::sync::RwLock;
 use tracing::info;
 
+/// Total number of load attempts `load_graph_from_surrealdb` makes (the
+/// first attempt plus reconnect-and-retry attempts) before giving up.
+const MAX_LOAD_RETRIES: u32 = 3;
+const RETRY_BACKOFF_BASE_MS: u64 = 200;
+
+/// A managed, reusable SurrealDB connection. Established once (lazily) and
+/// reused across reloads instead of opening a brand-new WebSocket, signing
+/// in, and selecting the namespace on every call. Supports transparent
+/// reconnect-on-failure.
+pub struct SurrealConnection {
+    inner: RwLock<Option<Surreal<Client>>>,
+}
+
+impl SurrealConnection {
+    pub fn new() -> Self {
+        SurrealConnection {
+            inner: RwLock::new(None),
+        }
+    }
+
+    /// Return the current connection if it's healthy, otherwise establish a
+    /// fresh one (re-running signin and namespace/database selection).
+    pub async fn get_or_connect(&self, config: &Config) -> Result<Surreal<Client>> {
+        {
+            let guard = self.inner.read().await;
+            if let Some(db) = guard.as_ref() {
+                if db.health().await.is_ok() {
+                    return Ok(db.clone());
+                }
+            }
+        }
+
+        self.reconnect(config).await
+    }
+
+    /// Force a fresh connection: re-runs `signin` and `use_ns`/`use_db`, and
+    /// replaces the cached handle.
+    pub async fn reconnect(&self, config: &Config) -> Result<Surreal<Client>> {
+        let db = connect_and_authenticate(config).await?;
+        let mut guard = self.inner.write().await;
+        *guard = Some(db.clone());
+        Ok(db)
+    }
+}
+
+impl Default for SurrealConnection {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn connect_and_authenticate(config: &Config) -> Result<Surreal<Client>> {
+    let db = Surreal::new::<Ws>(&config.surrealdb_url)
+        .await
+        .context("Failed to connect to SurrealDB")?;
+
+    db.signin(Root {
+        username: &config.surrealdb_user,
+        password: &config.surrealdb_pass,
+    })
+    .await
+    .context("Failed to authenticate with SurrealDB")?;
+
+    db.use_ns("veds").use_db("production").await?;
+
+    Ok(db)
+}
+
 /// Raw node data from SurrealDB
 #[derive(Debug, Deserialize)]
 struct RawNode {
@@ -75,26 +144,46 @@ struct RawCarrier {
     sanctioned: Option<bool>,
 }
 
-/// Load the transport graph from SurrealDB
-pub async fn load_graph_from_surrealdb(config: &Config) -> Result<TransportGraph> {
+/// Load the transport graph from SurrealDB, reusing the managed connection
+/// and transparently reconnecting (with backoff) if the socket was dropped.
+pub async fn load_graph_from_surrealdb(
+    conn: &SurrealConnection,
+    config: &Config,
+) -> Result<TransportGraph> {
     let start = std::time::Instant::now();
 
-    // Connect to SurrealDB
-    let db = Surreal::new::<Ws>(&config.surrealdb_url)
-        .await
-        .context("Failed to connect to SurrealDB")?;
+    let mut last_err = None;
+    for attempt in 0..MAX_LOAD_RETRIES {
+        let db = if attempt == 0 {
+            conn.get_or_connect(config).await?
+        } else {
+            let backoff = RETRY_BACKOFF_BASE_MS * 2u64.pow(attempt - 1);
+            tokio::time::sleep(std::time::Duration::from_millis(backoff)).await;
+            conn.reconnect(config).await?
+        };
 
-    // Sign in
-    db.signin(Root {
-        username: &config.surrealdb_user,
-        password: &config.surrealdb_pass,
-    })
-    .await
-    .context("Failed to authenticate with SurrealDB")?;
+        match load_graph_with_db(&db).await {
+            Ok(mut graph) => {
+                graph.load_time_ms = start.elapsed().as_millis() as u64;
+                graph.loaded_at = chrono::Utc::now();
+                return Ok(graph);
+            }
+            Err(e) => {
+                tracing::warn!(
+                    attempt = attempt + 1,
+                    "Graph load attempt failed: {}. Retrying.",
+                    e
+                );
+                last_err = Some(e);
+            }
+        }
+    }
 
-    // Select namespace and database
-    db.use_ns("veds").use_db("production").await?;
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("Failed to load graph from SurrealDB")))
+}
 
+/// Run the node/edge queries against an already-authenticated connection.
+async fn load_graph_with_db(db: &Surreal<Client>) -> Result<TransportGraph> {
     let mut graph = TransportGraph::new();
 
     // Load nodes
@@ -105,32 +194,7 @@ pub async fn load_graph_from_surrealdb(config: &Config) -> Result<TransportGraph
 
     info!(count = nodes.len(), "Loaded nodes from SurrealDB");
 
-    for raw_node in nodes {
-        let modes: Vec<TransportMode> = raw_node
-            .modes
-            .iter()
-            .filter_map(|m| parse_mode(m))
-            .collect();
-
-        let (lon, lat) = raw_node
-            .port
-            .location
-            .map(|l| l.coordinates)
-            .unwrap_or((0.0, 0.0));
-
-        let node = TransportNode {
-            id: raw_node.id.to_string(),
-            code: raw_node.code,
-            name: raw_node.port.name,
-            country_code: raw_node.port.country.code,
-            lat,
-            lon,
-            modes,
-            avg_dwell_hours: raw_node.port.avg_dwell_hours.unwrap_or(24.0),
-        };
-
-        graph.add_node(node);
-    }
+    graph.bulk_load_nodes(nodes.into_iter().map(raw_node_to_node));
 
     // Load edges
     let edges: Vec<RawEdge> = db
@@ -141,37 +205,161 @@ pub async fn load_graph_from_surrealdb(config: &Config) -> Result<TransportGraph
     info!(count = edges.len(), "Loaded edges from SurrealDB");
 
     for raw_edge in edges {
-        let Some(mode) = parse_mode(&raw_edge.mode) else {
-            continue;
-        };
+        let from_code = raw_edge.from_node.code.clone();
+        let to_code = raw_edge.to_node.code.clone();
+        if let Some(edge) = raw_edge_to_edge(raw_edge) {
+            graph.add_edge(&from_code, &to_code, edge);
+        }
+    }
 
-        let edge = TransportEdge {
-            id: raw_edge.id.to_string(),
-            code: raw_edge.code,
-            mode,
-            carrier_code: raw_edge.carrier.code,
-            carrier_name: raw_edge.carrier.name,
-            distance_km: raw_edge.distance_km,
-            base_cost_usd: Decimal::from_f64_retain(raw_edge.base_cost_usd)
-                .unwrap_or(Decimal::ZERO),
-            cost_per_kg: Decimal::from_f64_retain(raw_edge.cost_per_kg_usd.unwrap_or(0.0))
-                .unwrap_or(Decimal::ZERO),
-            transit_hours: raw_edge.transit_hours,
-            carbon_per_tonne_km: raw_edge.carbon_kg_per_tonne_km,
-            carrier_wage_cents: raw_edge.carrier.avg_wage_cents_hourly.unwrap_or(1500),
-            carrier_safety_rating: raw_edge.carrier.safety_rating.unwrap_or(3),
-            carrier_unionized: raw_edge.carrier.unionized.unwrap_or(false),
-            carrier_sanctioned: raw_edge.carrier.sanctioned.unwrap_or(false),
-            active: raw_edge.active.unwrap_or(true),
-        };
+    Ok(graph)
+}
 
-        graph.add_edge(&raw_edge.from_node.code, &raw_edge.to_node.code, edge);
+/// Convert a raw SurrealDB node row into a `TransportNode`
+fn raw_node_to_node(raw_node: RawNode) -> TransportNode {
+    let modes: Vec<TransportMode> = raw_node
+        .modes
+        .iter()
+        .filter_map(|m| parse_mode(m))
+        .collect();
+
+    let (lon, lat) = raw_node
+        .port
+        .location
+        .map(|l| l.coordinates)
+        .unwrap_or((0.0, 0.0));
+
+    TransportNode {
+        id: raw_node.id.to_string(),
+        code: raw_node.code,
+        name: raw_node.port.name,
+        country_code: raw_node.port.country.code,
+        lat,
+        lon,
+        modes,
+        avg_dwell_hours: raw_node.port.avg_dwell_hours.unwrap_or(24.0),
     }
+}
 
-    graph.load_time_ms = start.elapsed().as_millis() as u64;
-    graph.loaded_at = chrono::Utc::now();
+/// Convert a raw SurrealDB edge row into a `TransportEdge`. Returns `None`
+/// if the row carries an unrecognized transport mode.
+fn raw_edge_to_edge(raw_edge: RawEdge) -> Option<TransportEdge> {
+    let mode = parse_mode(&raw_edge.mode)?;
+
+    Some(TransportEdge {
+        id: raw_edge.id.to_string(),
+        code: raw_edge.code,
+        mode,
+        carrier_code: raw_edge.carrier.code,
+        carrier_name: raw_edge.carrier.name,
+        distance_km: raw_edge.distance_km,
+        base_cost_usd: Decimal::from_f64_retain(raw_edge.base_cost_usd).unwrap_or(Decimal::ZERO),
+        cost_per_kg: Decimal::from_f64_retain(raw_edge.cost_per_kg_usd.unwrap_or(0.0))
+            .unwrap_or(Decimal::ZERO),
+        transit_hours: raw_edge.transit_hours,
+        carbon_per_tonne_km: raw_edge.carbon_kg_per_tonne_km,
+        carrier_wage_cents: raw_edge.carrier.avg_wage_cents_hourly.unwrap_or(1500),
+        carrier_safety_rating: raw_edge.carrier.safety_rating.unwrap_or(3),
+        carrier_unionized: raw_edge.carrier.unionized.unwrap_or(false),
+        carrier_sanctioned: raw_edge.carrier.sanctioned.unwrap_or(false),
+        active: raw_edge.active.unwrap_or(true),
+    })
+}
 
-    Ok(graph)
+/// Stream incremental create/update/delete deltas for `transport_node` and
+/// `transport_edge` via SurrealDB `LIVE SELECT`, applying each one to the
+/// in-memory graph as it arrives instead of rebuilding the whole graph.
+/// Takes the graph write lock only for the duration of a single delta.
+/// `reload_graph` remains the full-resync path used on startup or whenever
+/// this stream drops.
+pub async fn watch_live_graph_updates(state: std::sync::Arc<crate::AppState>) {
+    use futures_util::StreamExt;
+    use surrealdb::Action;
+
+    const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+    loop {
+        let db = match state.surreal.get_or_connect(&state.config).await {
+            Ok(db) => db,
+            Err(e) => {
+                tracing::warn!("Live graph query connection failed: {}", e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        let nodes = db.select("transport_node").live().await;
+        let edges = db.select("transport_edge").live().await;
+
+        let (mut node_stream, mut edge_stream) = match (nodes, edges) {
+            (Ok(n), Ok(e)) => (n, e),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::warn!(
+                    "Failed to start live graph queries ({}), falling back to periodic reload",
+                    e
+                );
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        info!("Subscribed to SurrealDB live graph updates");
+
+        loop {
+            tokio::select! {
+                node = node_stream.next() => {
+                    let Some(Ok(notification)) = node else { break };
+                    match notification.action {
+                        Action::Create | Action::Update => {
+                            let node = raw_node_to_node(notification.data);
+                            state.graph.write().await.upsert_node(node);
+                            // A node changing can add/remove edges at its
+                            // boundary, which a single `invalidate_edge`
+                            // can't pin down — drop every cached tree.
+                            state.precomputed_router.write().unwrap().invalidate_all();
+                        }
+                        Action::Delete => {
+                            state.graph.write().await.remove_node(&notification.data.code);
+                            state.precomputed_router.write().unwrap().invalidate_all();
+                        }
+                        _ => {}
+                    }
+                }
+                edge = edge_stream.next() => {
+                    let Some(Ok(notification)) = edge else { break };
+                    match notification.action {
+                        Action::Create | Action::Update => {
+                            let from_code = notification.data.from_node.code.clone();
+                            let to_code = notification.data.to_node.code.clone();
+                            let edge_code = notification.data.code.clone();
+                            if let Some(edge) = raw_edge_to_edge(notification.data) {
+                                state.graph.write().await.upsert_edge(&from_code, &to_code, edge);
+                            }
+                            // Covers `active`/`carrier_sanctioned`/pricing
+                            // changes too: any of those is still an Update,
+                            // and `content_hash` doesn't cover `active` or
+                            // `carrier_sanctioned`, so the cached trees must
+                            // be dropped explicitly here rather than relying
+                            // on a hash comparison.
+                            state.precomputed_router.write().unwrap().invalidate_edge(&edge_code);
+                        }
+                        Action::Delete => {
+                            state.graph.write().await.remove_edge_by_code(&notification.data.code);
+                            state
+                                .precomputed_router
+                                .write()
+                                .unwrap()
+                                .invalidate_edge(&notification.data.code);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        tracing::warn!("Live graph query stream ended, reconnecting");
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
 }
 
 fn parse_mode(s: &str) -> Option<TransportMode> {
@@ -216,3 +404,131 @@ pub async fn load_constraints_from_dragonfly(
 
     Ok(cache)
 }
+
+/// Load runtime-registered custom constraint rules from Dragonfly. Each
+/// `constraint:custom:<id>` key holds a JSON-serialized `CustomConstraintRule`,
+/// so operators can add compliance rules (new sanctions lists, region-specific
+/// caps) by writing data rather than shipping a new binary.
+pub async fn load_custom_rules_from_dragonfly(
+    redis: &mut redis::aio::ConnectionManager,
+) -> Result<Vec<crate::constraints::CustomConstraintRule>> {
+    use redis::AsyncCommands;
+
+    let keys: Vec<String> = redis
+        .keys("constraint:custom:*")
+        .await
+        .unwrap_or_default();
+
+    let mut rules = Vec::with_capacity(keys.len());
+    for key in keys {
+        match redis.get::<_, String>(&key).await {
+            Ok(raw) => match serde_json::from_str(&raw) {
+                Ok(rule) => rules.push(rule),
+                Err(e) => tracing::warn!(key, "Failed to parse custom constraint rule: {}", e),
+            },
+            Err(e) => tracing::warn!(key, "Failed to read custom constraint rule: {}", e),
+        }
+    }
+
+    Ok(rules)
+}
+
+/// Keep the constraint cache live by subscribing to Dragonfly keyspace
+/// notifications on `constraint:min_wage:*` and
+/// `constraint:sanctioned:carriers`. On any change, re-reads only the
+/// affected key and atomically swaps it into the cache, avoiding a full
+/// re-scan on every request. Requires the Dragonfly instance to have
+/// `notify-keyspace-events` enabled for generic and set commands (e.g. `KEA`).
+/// Runs forever, reconnecting with a fixed delay if the pubsub connection drops.
+pub async fn watch_constraint_cache(
+    redis_client: redis::Client,
+    cache: std::sync::Arc<RwLock<crate::constraints::ConstraintCache>>,
+) {
+    use futures_util::StreamExt;
+
+    const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+    loop {
+        let conn = match redis_client.get_async_connection().await {
+            Ok(conn) => conn,
+            Err(e) => {
+                tracing::warn!("Failed to open constraint pubsub connection: {}", e);
+                tokio::time::sleep(RECONNECT_DELAY).await;
+                continue;
+            }
+        };
+
+        let mut pubsub = conn.into_pubsub();
+        if let Err(e) = pubsub
+            .psubscribe(&[
+                "__keyspace@*__:constraint:min_wage:*",
+                "__keyspace@*__:constraint:sanctioned:carriers",
+            ])
+            .await
+        {
+            tracing::warn!("Failed to subscribe to constraint keyspace events: {}", e);
+            tokio::time::sleep(RECONNECT_DELAY).await;
+            continue;
+        }
+
+        info!("Subscribed to Dragonfly constraint keyspace notifications");
+
+        let mut messages = pubsub.on_message();
+        while let Some(msg) = messages.next().await {
+            let Some(key) = parse_keyspace_channel(msg.get_channel_name()) else {
+                continue;
+            };
+            refresh_constraint_key(&redis_client, &cache, &key).await;
+        }
+
+        tracing::warn!("Constraint pubsub stream ended, reconnecting");
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Extract the watched key name out of a `__keyspace@<db>__:<key>` channel.
+fn parse_keyspace_channel(channel: &str) -> Option<String> {
+    channel.find("__:").map(|idx| channel[idx + 3..].to_string())
+}
+
+/// Re-read a single changed constraint key and swap it into the live cache.
+async fn refresh_constraint_key(
+    client: &redis::Client,
+    cache: &std::sync::Arc<RwLock<crate::constraints::ConstraintCache>>,
+    key: &str,
+) {
+    use redis::AsyncCommands;
+
+    let Ok(mut conn) = client.get_async_connection().await else {
+        return;
+    };
+
+    if key == "constraint:sanctioned:carriers" {
+        if let Ok(members) = conn.smembers::<_, Vec<String>>(key).await {
+            let mut cache = cache.write().await;
+            cache.sanctioned_carriers = members.into_iter().collect();
+            info!("Refreshed sanctioned carrier list from Dragonfly");
+        }
+        return;
+    }
+
+    let Some(country) = key.strip_prefix("constraint:min_wage:") else {
+        return;
+    };
+
+    match conn.get::<_, Option<i32>>(key).await {
+        Ok(Some(wage)) => {
+            let mut cache = cache.write().await;
+            cache.min_wages.insert(country.to_string(), wage);
+            info!(country, wage, "Refreshed minimum wage from Dragonfly");
+        }
+        Ok(None) => {
+            let mut cache = cache.write().await;
+            cache.min_wages.remove(country);
+            info!(country, "Removed minimum wage (key deleted in Dragonfly)");
+        }
+        Err(e) => {
+            tracing::warn!(country, "Failed to refresh minimum wage: {}", e);
+        }
+    }
+}
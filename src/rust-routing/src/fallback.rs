@@ -0,0 +1,31 @@
+//! Primary/Fallback Source Helper
+//!
+//! `graph_source::FallbackGraphSource` and
+//! `constraint_source::FallbackRuleSource` both wrap a primary and a
+//! secondary source behind their own trait (`GraphSource`/
+//! `ConstraintRuleSource`), but the actual try-then-fall-back behavior —
+//! attempt the primary, warn and fall back to the secondary on failure —
+//! is identical between them. This is that shared behavior, factored out
+//! so each wrapper only has to supply its own trait call.
+pub(crate) async fn try_with_fallback<T, E, Fut1, Fut2>(
+    source_kind: &str,
+    primary: Fut1,
+    fallback: impl FnOnce() -> Fut2,
+) -> Result<T, E>
+where
+    Fut1: std::future::Future<Output = Result<T, E>>,
+    Fut2: std::future::Future<Output = Result<T, E>>,
+    E: std::fmt::Display,
+{
+    match primary.await {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            tracing::warn!(
+                "Primary {} failed ({}), falling back to last-good snapshot",
+                source_kind,
+                e
+            );
+            fallback().await
+        }
+    }
+}
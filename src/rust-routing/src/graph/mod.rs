@@ -5,11 +5,303 @@
 
 use petgraph::graph::{DiGraph, NodeIndex};
 use petgraph::algo::dijkstra;
+use petgraph::visit::{EdgeFiltered, EdgeRef};
+use rstar::{RTree, RTreeObject, PointDistance, AABB};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use chrono::{DateTime, Utc};
 
+/// An entry in `shortest_path_astar`'s open set, ordered by `g + h` so the
+/// most promising node is always popped first. `BinaryHeap` is a max-heap,
+/// so the ordering below is reversed to make it behave as a min-heap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AstarState {
+    priority: f64,
+    node: NodeIndex,
+}
+
+impl Eq for AstarState {}
+
+impl Ord for AstarState {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .partial_cmp(&self.priority)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for AstarState {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A candidate alternative route awaiting consideration in
+/// `k_shortest_paths`'s Yen's-algorithm candidate pool, ordered by total
+/// cost so the cheapest is always popped first (reversed `Ord`, same
+/// min-heap-over-`BinaryHeap` idiom as `AstarState`).
+struct KShortestCandidate<'a> {
+    cost: Decimal,
+    signature: Vec<String>,
+    path: (Vec<NodeIndex>, Vec<&'a TransportEdge>, Decimal),
+}
+
+impl PartialEq for KShortestCandidate<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for KShortestCandidate<'_> {}
+
+impl Ord for KShortestCandidate<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost)
+    }
+}
+
+impl PartialOrd for KShortestCandidate<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Distinctness signature for a path in `k_shortest_paths`: the ordered
+/// sequence of edge codes it traverses.
+fn edge_signature(edges: &[&TransportEdge]) -> Vec<String> {
+    edges.iter().map(|e| e.code.clone()).collect()
+}
+
+/// One label in `pareto_paths`'s label-setting search: the four accumulated
+/// objectives along some path from the search origin to `node`, plus enough
+/// bookkeeping to compute the mean labor score and reconstruct the path.
+#[derive(Debug, Clone, Copy)]
+struct ParetoLabel {
+    cost: Decimal,
+    carbon_kg: f64,
+    transit_hours: f64,
+    labor_sum: f64,
+    hops: u32,
+}
+
+impl ParetoLabel {
+    /// Mean labor score across the hops accumulated so far (0.0 at the
+    /// root, before any edge has been taken).
+    fn labor_avg(&self) -> f64 {
+        if self.hops == 0 {
+            0.0
+        } else {
+            self.labor_sum / self.hops as f64
+        }
+    }
+
+    /// True if `self` is at least as good as `other` on every objective and
+    /// strictly better on at least one. Cost, carbon, and transit time are
+    /// lower-is-better; labor score is higher-is-better.
+    fn dominates(&self, other: &Self) -> bool {
+        let no_worse = self.cost <= other.cost
+            && self.carbon_kg <= other.carbon_kg
+            && self.transit_hours <= other.transit_hours
+            && self.labor_avg() >= other.labor_avg();
+        let strictly_better = self.cost < other.cost
+            || self.carbon_kg < other.carbon_kg
+            || self.transit_hours < other.transit_hours
+            || self.labor_avg() > other.labor_avg();
+        no_worse && strictly_better
+    }
+
+    /// Normalized weighted-sum scalarization used only to order expansion
+    /// in `pareto_paths`'s priority queue; it has no bearing on which
+    /// labels ultimately survive as non-dominated.
+    fn scalarize(&self) -> f64 {
+        let cost_norm = self.cost.to_string().parse::<f64>().unwrap_or(0.0) / 1000.0;
+        let carbon_norm = self.carbon_kg / 1000.0;
+        let transit_norm = self.transit_hours / 24.0;
+        let labor_penalty = 1.0 - self.labor_avg();
+        0.25 * cost_norm + 0.25 * carbon_norm + 0.25 * transit_norm + 0.25 * labor_penalty
+    }
+}
+
+/// A `ParetoLabel` plus where it came from, so a surviving label at the
+/// destination can be walked back into an actual path.
+#[derive(Debug, Clone, Copy)]
+struct ParetoLabelEntry {
+    label: ParetoLabel,
+    node: NodeIndex,
+    predecessor: Option<usize>,
+    /// The specific edge taken from `predecessor`'s node to reach this
+    /// label, so path reconstruction uses exactly the edge this label
+    /// accumulated its objectives over — not just any edge connecting the
+    /// same two nodes (there can be several, e.g. competing carriers).
+    via_edge: Option<petgraph::graph::EdgeIndex>,
+}
+
+/// An entry in `pareto_paths`'s open set, ordered by `ParetoLabel::scalarize`
+/// so the most promising label is always popped first. `BinaryHeap` is a
+/// max-heap, so the ordering below is reversed to make it behave as a
+/// min-heap, the same trick as `AstarState`.
+#[derive(Debug, Clone, Copy)]
+struct ScalarizedEntry {
+    priority: f64,
+    arena_idx: usize,
+}
+
+impl PartialEq for ScalarizedEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for ScalarizedEntry {}
+
+impl Ord for ScalarizedEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .partial_cmp(&self.priority)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for ScalarizedEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// One non-dominated route found by `pareto_paths`: the accumulated
+/// objectives alongside the actual path and edges taken to achieve them.
+#[derive(Debug, Clone)]
+pub struct ParetoRoute<'a> {
+    pub path: Vec<NodeIndex>,
+    pub edges: Vec<&'a TransportEdge>,
+    pub total_cost_usd: Decimal,
+    pub total_carbon_kg: f64,
+    pub total_transit_hours: f64,
+    pub labor_score: f64,
+}
+
+/// Caller-supplied weights for `best_weighted_path`'s scalarized cost.
+/// Mirrors `OptimizeRequest`'s `cost_weight`/`time_weight`/`carbon_weight`/
+/// `labor_weight`, so a request's existing weighting can be reused here
+/// directly.
+#[derive(Debug, Clone, Copy)]
+pub struct PathWeights {
+    pub cost_weight: f64,
+    pub time_weight: f64,
+    pub carbon_weight: f64,
+    pub labor_weight: f64,
+}
+
+impl Default for PathWeights {
+    fn default() -> Self {
+        PathWeights {
+            cost_weight: 0.4,
+            time_weight: 0.3,
+            carbon_weight: 0.2,
+            labor_weight: 0.1,
+        }
+    }
+}
+
+/// A label in `fastest_path`'s expanded state space: a node plus the mode
+/// the path arrived on, since the transfer penalty for the next hop depends
+/// on it. `None` means "no incoming mode yet" (only true at the origin).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct TimeState {
+    node: NodeIndex,
+    incoming_mode: Option<TransportMode>,
+}
+
+/// An entry in `fastest_path`'s open set, ordered by total elapsed hours so
+/// far. `BinaryHeap` is a max-heap, so the ordering is reversed to behave
+/// as a min-heap, the same trick as `AstarState`.
+#[derive(Debug, Clone, Copy)]
+struct TimeEntry {
+    priority: f64,
+    state: TimeState,
+}
+
+impl PartialEq for TimeEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl Eq for TimeEntry {}
+
+impl Ord for TimeEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .priority
+            .partial_cmp(&self.priority)
+            .unwrap_or(Ordering::Equal)
+    }
+}
+
+impl PartialOrd for TimeEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// One hop in a `fastest_path` result: the node reached, the edge taken to
+/// reach it (`None` for the origin), and whether this hop required a modal
+/// transfer from whichever mode the path arrived on at the previous node.
+#[derive(Debug, Clone)]
+pub struct FastestPathHop<'a> {
+    pub node: NodeIndex,
+    pub edge: Option<&'a TransportEdge>,
+    pub mode_transfer: bool,
+}
+
+/// Great-circle distance between two `(lat, lon)` points, in kilometers.
+pub(crate) fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    const EARTH_RADIUS_KM: f64 = 6371.0;
+    let (lat1r, lat2r) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = (lat2 - lat1).to_radians();
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1r.cos() * lat2r.cos() * (dlon / 2.0).sin().powi(2);
+    let c = 2.0 * a.sqrt().atan2((1.0 - a).sqrt());
+    EARTH_RADIUS_KM * c
+}
+
+/// An R-tree entry for `TransportGraph`'s spatial index: just enough to
+/// resolve a nearest/within-radius hit back to the real `TransportNode`
+/// (by code) and to apply a mode filter without a second graph lookup.
+#[derive(Debug, Clone)]
+struct IndexedNode {
+    code: String,
+    lat: f64,
+    lon: f64,
+    modes: Vec<TransportMode>,
+}
+
+impl RTreeObject for IndexedNode {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_point([self.lon, self.lat])
+    }
+}
+
+impl PointDistance for IndexedNode {
+    /// Squared haversine distance in km, not rstar's default squared
+    /// Euclidean-on-degrees, so nearest/within-radius queries reflect true
+    /// geographic proximity rather than the distortion plain lat/lon
+    /// Euclidean distance suffers near the poles and across the
+    /// antimeridian.
+    fn distance_2(&self, point: &[f64; 2]) -> f64 {
+        let km = haversine_km(self.lat, self.lon, point[1], point[0]);
+        km * km
+    }
+}
+
 /// Transport mode
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
@@ -117,12 +409,110 @@ impl TransportEdge {
     }
 }
 
+/// Why a freshly loaded graph snapshot failed validation and was rejected
+/// in favor of keeping the previous last-good graph.
+#[derive(Debug, Clone)]
+pub struct GraphValidationError(pub String);
+
+impl std::fmt::Display for GraphValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for GraphValidationError {}
+
+/// Compliance/ESG and regulatory constraints enforced at edge-relaxation
+/// time by every path-finding method on `TransportGraph`, so a forbidden
+/// edge (sanctioned carrier, too few safety stars, wrong mode, etc.) is
+/// never expanded, rather than being filtered out of results after the
+/// fact. `Default` means "no constraints" — every edge is compliant.
+#[derive(Debug, Clone, Default)]
+pub struct RouteConstraints {
+    pub min_safety_rating: Option<i32>,
+    pub exclude_sanctioned: bool,
+    pub require_unionized: bool,
+    pub exclude_inactive: bool,
+    /// Empty means no mode restriction, mirroring the empty-means-all-allowed
+    /// convention `OptimizeRequest::allowed_modes` uses elsewhere.
+    pub allowed_modes: HashSet<TransportMode>,
+    pub max_transit_hours: Option<f64>,
+    /// Per-edge carbon budget for the shipment weight passed to
+    /// `is_compliant`. Ignored when that weight is `None` — `fastest_path`
+    /// has no cargo-weight context to compute carbon from, so it cannot
+    /// enforce this particular constraint.
+    pub max_carbon_budget: Option<f64>,
+}
+
+impl RouteConstraints {
+    /// Whether `edge` may be expanded during relaxation under these
+    /// constraints. `weight_kg` is the shipment weight, where known; pass
+    /// `None` to skip the carbon-budget check when no weight is available.
+    pub fn is_compliant(&self, edge: &TransportEdge, weight_kg: Option<f64>) -> bool {
+        if self.exclude_inactive && !edge.active {
+            return false;
+        }
+        if self.exclude_sanctioned && edge.carrier_sanctioned {
+            return false;
+        }
+        if self.require_unionized && !edge.carrier_unionized {
+            return false;
+        }
+        if let Some(min_rating) = self.min_safety_rating {
+            if edge.carrier_safety_rating < min_rating {
+                return false;
+            }
+        }
+        if !self.allowed_modes.is_empty() && !self.allowed_modes.contains(&edge.mode) {
+            return false;
+        }
+        if let Some(max_hours) = self.max_transit_hours {
+            if edge.transit_hours > max_hours {
+                return false;
+            }
+        }
+        if let (Some(max_carbon), Some(weight_kg)) = (self.max_carbon_budget, weight_kg) {
+            if edge.calculate_carbon(weight_kg) > max_carbon {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Distinguishes "no route exists at all" from "a route exists, but none of
+/// them satisfy the given `RouteConstraints`" — the latter is a compliance
+/// finding a caller should surface differently (e.g. to an ESG/regulatory
+/// team) than a plain routing failure. Returned by `shortest_path_compliant`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RouteSearchError {
+    NoPathExists,
+    NoCompliantPath,
+}
+
+impl std::fmt::Display for RouteSearchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RouteSearchError::NoPathExists => write!(f, "no path exists between the requested nodes"),
+            RouteSearchError::NoCompliantPath => {
+                write!(f, "a path exists, but none of them satisfy the given route constraints")
+            }
+        }
+    }
+}
+
+impl std::error::Error for RouteSearchError {}
+
 /// The transport graph
 pub struct TransportGraph {
     /// The underlying directed graph
     graph: DiGraph<TransportNode, TransportEdge>,
     /// Map from node code to graph index
     node_index: HashMap<String, NodeIndex>,
+    /// R-tree over all node coordinates, for snapping raw GPS/address
+    /// coordinates to the nearest node. Rebuilt on every mutation that can
+    /// change node coordinates or membership; see `rebuild_spatial_index`.
+    spatial_index: RTree<IndexedNode>,
     /// When the graph was last loaded
     pub loaded_at: DateTime<Utc>,
     /// Load time in milliseconds
@@ -135,6 +525,7 @@ impl TransportGraph {
         TransportGraph {
             graph: DiGraph::new(),
             node_index: HashMap::new(),
+            spatial_index: RTree::new(),
             loaded_at: Utc::now(),
             load_time_ms: 0,
         }
@@ -145,9 +536,79 @@ impl TransportGraph {
         let code = node.code.clone();
         let idx = self.graph.add_node(node);
         self.node_index.insert(code, idx);
+        self.rebuild_spatial_index();
         idx
     }
 
+    /// Add many nodes at once, rebuilding the spatial index only once at
+    /// the end instead of once per node. Reserved for batch loads (e.g.
+    /// `GraphSource` implementations) — single-node and live-update paths
+    /// still go through `add_node`/`upsert_node`, where a per-call rebuild
+    /// is the right cost for immediate index consistency. Without this,
+    /// loading a graph of N nodes one `add_node` at a time is O(N^2) in the
+    /// spatial index alone.
+    pub fn bulk_load_nodes(&mut self, nodes: impl IntoIterator<Item = TransportNode>) -> Vec<NodeIndex> {
+        let indices = nodes
+            .into_iter()
+            .map(|node| {
+                let code = node.code.clone();
+                let idx = self.graph.add_node(node);
+                self.node_index.insert(code, idx);
+                idx
+            })
+            .collect();
+        self.rebuild_spatial_index();
+        indices
+    }
+
+    /// Rebuild the spatial index from scratch over the current node set.
+    /// A full bulk-load rather than an incremental insert: simple and cheap
+    /// enough at this graph's scale, and avoids tracking per-node deletes
+    /// that `remove_node`'s index-swapping would otherwise complicate.
+    fn rebuild_spatial_index(&mut self) {
+        let entries: Vec<IndexedNode> = self
+            .graph
+            .node_weights()
+            .map(|n| IndexedNode {
+                code: n.code.clone(),
+                lat: n.lat,
+                lon: n.lon,
+                modes: n.modes.clone(),
+            })
+            .collect();
+        self.spatial_index = RTree::bulk_load(entries);
+    }
+
+    /// Find the nearest node to an arbitrary `(lat, lon)` coordinate, by true
+    /// geographic distance. When `filter_modes` is non-empty, only nodes
+    /// supporting at least one of the given modes are considered (e.g. the
+    /// nearest *maritime-capable* node), mirroring the empty-means-no-filter
+    /// convention used by `OptimizeRequest::allowed_modes` elsewhere.
+    pub fn nearest_node(&self, lat: f64, lon: f64, filter_modes: &[TransportMode]) -> Option<&TransportNode> {
+        let query = [lon, lat];
+        let code = if filter_modes.is_empty() {
+            &self.spatial_index.nearest_neighbor(&query)?.code
+        } else {
+            &self
+                .spatial_index
+                .nearest_neighbor_iter(&query)
+                .find(|n| n.modes.iter().any(|m| filter_modes.contains(m)))?
+                .code
+        };
+        self.get_node(code)
+    }
+
+    /// All nodes within `radius_km` of an arbitrary `(lat, lon)` coordinate,
+    /// by true geographic distance rather than Euclidean-on-degrees.
+    pub fn nodes_within_km(&self, lat: f64, lon: f64, radius_km: f64) -> Vec<&TransportNode> {
+        let query = [lon, lat];
+        let radius_sq = radius_km * radius_km;
+        self.spatial_index
+            .locate_within_distance(query, radius_sq)
+            .filter_map(|n| self.get_node(&n.code))
+            .collect()
+    }
+
     /// Add an edge to the graph
     pub fn add_edge(&mut self, from_code: &str, to_code: &str, edge: TransportEdge) -> bool {
         if let (Some(&from_idx), Some(&to_idx)) = (
@@ -161,6 +622,68 @@ impl TransportGraph {
         }
     }
 
+    /// Upsert a node by code: updates it in place if it already exists
+    /// (keeping its graph index and incident edges), otherwise inserts it.
+    /// Used to apply incremental deltas from a SurrealDB live query.
+    pub fn upsert_node(&mut self, node: TransportNode) -> NodeIndex {
+        if let Some(&idx) = self.node_index.get(&node.code) {
+            self.graph[idx] = node;
+            self.rebuild_spatial_index();
+            idx
+        } else {
+            self.add_node(node)
+        }
+    }
+
+    /// Remove a node (and its incident edges) by code.
+    pub fn remove_node(&mut self, code: &str) -> bool {
+        let Some(idx) = self.node_index.remove(code) else {
+            return false;
+        };
+
+        // `DiGraph::remove_node` swaps the last node into `idx`'s slot, so
+        // the node_index entry for whichever code held the last index needs
+        // to follow it to keep the map consistent.
+        let last_idx = NodeIndex::new(self.graph.node_count() - 1);
+        self.graph.remove_node(idx);
+        if idx != last_idx {
+            if let Some(moved_code) = self.graph.node_weight(idx).map(|n| n.code.clone()) {
+                self.node_index.insert(moved_code, idx);
+            }
+        }
+
+        self.rebuild_spatial_index();
+        true
+    }
+
+    /// Upsert an edge by code: updates the edge weight in place if an edge
+    /// with this code already exists, otherwise inserts a new edge. Used to
+    /// apply incremental deltas from a SurrealDB live query.
+    pub fn upsert_edge(&mut self, from_code: &str, to_code: &str, edge: TransportEdge) -> bool {
+        if let Some(edge_idx) = self.find_edge_index_by_code(&edge.code) {
+            if let Some(weight) = self.graph.edge_weight_mut(edge_idx) {
+                *weight = edge;
+                return true;
+            }
+        }
+        self.add_edge(from_code, to_code, edge)
+    }
+
+    /// Remove an edge by its code.
+    pub fn remove_edge_by_code(&mut self, code: &str) -> bool {
+        let Some(edge_idx) = self.find_edge_index_by_code(code) else {
+            return false;
+        };
+        self.graph.remove_edge(edge_idx);
+        true
+    }
+
+    fn find_edge_index_by_code(&self, code: &str) -> Option<petgraph::graph::EdgeIndex> {
+        self.graph
+            .edge_indices()
+            .find(|&idx| self.graph[idx].code == code)
+    }
+
     /// Get node by code
     pub fn get_node(&self, code: &str) -> Option<&TransportNode> {
         self.node_index.get(code).map(|&idx| &self.graph[idx])
@@ -190,26 +713,640 @@ impl TransportGraph {
         counts
     }
 
-    /// Find shortest path by cost using Dijkstra
+    /// Find shortest path by cost using plain Dijkstra, with no path
+    /// reconstruction. Used as `shortest_path_astar`'s fallback when a node
+    /// on the route has no usable coordinates to build a heuristic from.
     pub fn shortest_path_by_cost(
         &self,
         from: &str,
         to: &str,
         weight_kg: f64,
+        constraints: &RouteConstraints,
     ) -> Option<(Vec<NodeIndex>, Decimal)> {
         let from_idx = self.get_node_index(from)?;
         let to_idx = self.get_node_index(to)?;
 
-        let costs = dijkstra(&self.graph, from_idx, Some(to_idx), |e| {
+        // Share the same compliance predicate every other search method
+        // uses, via petgraph's filtered-view adapter rather than cloning a
+        // compliant sub-graph.
+        let filtered = EdgeFiltered::from_fn(&self.graph, |edge_ref| {
+            constraints.is_compliant(edge_ref.weight(), Some(weight_kg))
+        });
+
+        let costs = dijkstra(&filtered, from_idx, Some(to_idx), |e| {
             e.weight().calculate_cost(weight_kg)
         });
 
         costs.get(&to_idx).map(|&cost| {
-            // Reconstruct path (simplified - real impl would track parents)
+            // No predecessor tracking here; callers that need the actual
+            // path should use `shortest_path_astar` instead.
             (vec![from_idx, to_idx], cost)
         })
     }
 
+    /// Find the lowest-cost path using A* with an admissible great-circle
+    /// heuristic, returning the full ordered node path, the edge taken for
+    /// each hop, and the total cost. Returns `None` (rather than searching)
+    /// if either endpoint has no usable coordinates (lat and lon both
+    /// exactly 0.0), since the heuristic would be meaningless there —
+    /// callers that still want a cost in that case should fall back to
+    /// `shortest_path_by_cost` themselves (as `shortest_path_compliant`
+    /// does for its "does any path exist" classification).
+    pub fn shortest_path_astar(
+        &self,
+        from: &str,
+        to: &str,
+        weight_kg: f64,
+        constraints: &RouteConstraints,
+    ) -> Option<(Vec<NodeIndex>, Vec<&TransportEdge>, Decimal)> {
+        self.shortest_path_astar_excluding(from, to, weight_kg, &HashSet::new(), &HashSet::new(), constraints)
+    }
+
+    /// Like `shortest_path_astar`, but distinguishes "no path exists at all"
+    /// from "a path exists, but none of them satisfy `constraints`": it
+    /// searches once under `constraints`, and if that fails, searches again
+    /// unconstrained purely to classify the failure. The classification
+    /// search uses `shortest_path_by_cost`, not `shortest_path_astar`,
+    /// since `shortest_path_astar` returns `None` whenever either endpoint
+    /// lacks coordinates regardless of whether a path exists — using it
+    /// here would misreport `NoPathExists` for a perfectly reachable
+    /// coordinate-less node.
+    pub fn shortest_path_compliant(
+        &self,
+        from: &str,
+        to: &str,
+        weight_kg: f64,
+        constraints: &RouteConstraints,
+    ) -> Result<(Vec<NodeIndex>, Vec<&TransportEdge>, Decimal), RouteSearchError> {
+        if let Some(result) = self.shortest_path_astar(from, to, weight_kg, constraints) {
+            return Ok(result);
+        }
+        if self
+            .shortest_path_by_cost(from, to, weight_kg, &RouteConstraints::default())
+            .is_some()
+        {
+            Err(RouteSearchError::NoCompliantPath)
+        } else {
+            Err(RouteSearchError::NoPathExists)
+        }
+    }
+
+    /// Same search as `shortest_path_astar`, but treats `excluded_nodes` as
+    /// absent from the graph and `excluded_edge_codes` as absent edges.
+    /// Used by `k_shortest_paths` to search from a spur node without
+    /// retracing a previously found path's shared prefix.
+    fn shortest_path_astar_excluding(
+        &self,
+        from: &str,
+        to: &str,
+        weight_kg: f64,
+        excluded_nodes: &HashSet<NodeIndex>,
+        excluded_edge_codes: &HashSet<String>,
+        constraints: &RouteConstraints,
+    ) -> Option<(Vec<NodeIndex>, Vec<&TransportEdge>, Decimal)> {
+        let from_idx = self.get_node_index(from)?;
+        let to_idx = self.get_node_index(to)?;
+        if excluded_nodes.contains(&from_idx) || excluded_nodes.contains(&to_idx) {
+            return None;
+        }
+
+        let origin_node = &self.graph[from_idx];
+        let target_node = &self.graph[to_idx];
+        let has_coordinates = |n: &TransportNode| n.lat != 0.0 || n.lon != 0.0;
+        if !has_coordinates(origin_node) || !has_coordinates(target_node) {
+            return None;
+        }
+
+        // Admissible heuristic: great-circle distance to the target times
+        // the cheapest per-kg rate seen anywhere in the graph, so it never
+        // overestimates the true remaining cost to the target.
+        let min_rate = self
+            .graph
+            .edge_weights()
+            .map(|e| e.cost_per_kg)
+            .min()
+            .unwrap_or(Decimal::ZERO)
+            * Decimal::from_f64_retain(weight_kg).unwrap_or_default();
+        let min_rate_f64 = min_rate.to_string().parse::<f64>().unwrap_or(0.0);
+
+        let heuristic = |idx: NodeIndex| -> f64 {
+            let node = &self.graph[idx];
+            haversine_km(node.lat, node.lon, target_node.lat, target_node.lon) * min_rate_f64
+        };
+
+        let mut g_score: HashMap<NodeIndex, Decimal> = HashMap::new();
+        let mut predecessor: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut open: BinaryHeap<AstarState> = BinaryHeap::new();
+
+        g_score.insert(from_idx, Decimal::ZERO);
+        open.push(AstarState {
+            priority: heuristic(from_idx),
+            node: from_idx,
+        });
+
+        while let Some(AstarState { priority, node: current }) = open.pop() {
+            if current == to_idx {
+                let mut path = vec![current];
+                let mut cursor = current;
+                while let Some(&prev) = predecessor.get(&cursor) {
+                    path.push(prev);
+                    cursor = prev;
+                }
+                path.reverse();
+
+                let edges = path
+                    .windows(2)
+                    .filter_map(|pair| {
+                        self.graph
+                            .edges_connecting(pair[0], pair[1])
+                            .filter(|e| {
+                                !excluded_edge_codes.contains(&e.weight().code)
+                                    && constraints.is_compliant(e.weight(), Some(weight_kg))
+                            })
+                            .min_by_key(|e| e.weight().calculate_cost(weight_kg))
+                            .map(|e| e.weight())
+                    })
+                    .collect();
+
+                return Some((path, edges, g_score[&to_idx]));
+            }
+
+            // A node can be pushed onto `open` more than once with a stale
+            // priority (we never remove the old entry); skip it once a
+            // cheaper path has already settled it.
+            let current_g = g_score[&current];
+            let current_g_f64 = current_g.to_string().parse::<f64>().unwrap_or(f64::MAX);
+            if priority > current_g_f64 + heuristic(current) {
+                continue;
+            }
+
+            for edge_ref in self.graph.edges(current) {
+                let neighbor = edge_ref.target();
+                if excluded_nodes.contains(&neighbor)
+                    || excluded_edge_codes.contains(&edge_ref.weight().code)
+                    || !constraints.is_compliant(edge_ref.weight(), Some(weight_kg))
+                {
+                    continue;
+                }
+                let tentative_g = current_g + edge_ref.weight().calculate_cost(weight_kg);
+
+                if g_score.get(&neighbor).map_or(true, |&g| tentative_g < g) {
+                    g_score.insert(neighbor, tentative_g);
+                    predecessor.insert(neighbor, current);
+                    let tentative_g_f64 = tentative_g.to_string().parse::<f64>().unwrap_or(f64::MAX);
+                    open.push(AstarState {
+                        priority: tentative_g_f64 + heuristic(neighbor),
+                        node: neighbor,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find up to `k` loopless paths from `from` to `to`, ordered by
+    /// increasing cost, via Yen's algorithm layered on
+    /// `shortest_path_astar_excluding`. The first path is the plain
+    /// shortest path; each subsequent path is found by walking every "spur
+    /// node" along the previous path, excluding the edges already used by
+    /// any prior path that shares that spur's root (prefix) and excluding
+    /// the root's own intermediate nodes (to guarantee the result stays
+    /// loopless), then re-searching from the spur to the target. The
+    /// cheapest candidate produced this way becomes the next result, and a
+    /// `HashSet` of edge-code signatures guarantees every returned path is
+    /// distinct.
+    pub fn k_shortest_paths(
+        &self,
+        from: &str,
+        to: &str,
+        weight_kg: f64,
+        k: usize,
+        constraints: &RouteConstraints,
+    ) -> Vec<(Vec<NodeIndex>, Vec<&TransportEdge>, Decimal)> {
+        if k == 0 {
+            return vec![];
+        }
+
+        let Some(first_path) = self.shortest_path_astar(from, to, weight_kg, constraints) else {
+            return vec![];
+        };
+
+        let mut seen_signatures: HashSet<Vec<String>> = HashSet::new();
+        seen_signatures.insert(edge_signature(&first_path.1));
+        let mut found = vec![first_path];
+
+        let mut candidates: BinaryHeap<KShortestCandidate> = BinaryHeap::new();
+
+        while found.len() < k {
+            let prev_nodes = found[found.len() - 1].0.clone();
+            let prev_edges = &found[found.len() - 1].1;
+
+            for spur_pos in 0..prev_nodes.len() - 1 {
+                let spur_node = prev_nodes[spur_pos];
+                let root_nodes = &prev_nodes[..spur_pos];
+                let root_edges = &prev_edges[..spur_pos];
+
+                // Exclude every edge that leaves the spur node along any
+                // previously found path sharing this exact root prefix
+                // *through the spur node itself*, so the spur search is
+                // forced onto a genuinely different continuation.
+                let root_prefix_inclusive = &prev_nodes[..=spur_pos];
+                let mut excluded_edge_codes: HashSet<String> = HashSet::new();
+                for (path_nodes, path_edges, _) in &found {
+                    if path_nodes.len() > spur_pos + 1 && path_nodes[..=spur_pos] == *root_prefix_inclusive {
+                        excluded_edge_codes.insert(path_edges[spur_pos].code.clone());
+                    }
+                }
+
+                // Exclude the root's own intermediate nodes (not the spur
+                // node itself) so the spur search can't loop back into them.
+                let excluded_nodes: HashSet<NodeIndex> =
+                    root_nodes.iter().copied().collect();
+
+                let spur_code = &self.graph[spur_node].code;
+                let Some((spur_nodes, spur_edges, spur_cost)) = self.shortest_path_astar_excluding(
+                    spur_code,
+                    to,
+                    weight_kg,
+                    &excluded_nodes,
+                    &excluded_edge_codes,
+                    constraints,
+                ) else {
+                    continue;
+                };
+
+                let root_cost: Decimal = root_edges.iter().map(|e| e.calculate_cost(weight_kg)).sum();
+
+                let mut total_nodes = root_nodes.to_vec();
+                total_nodes.extend(spur_nodes);
+                let mut total_edges: Vec<&TransportEdge> = root_edges.to_vec();
+                total_edges.extend(spur_edges);
+
+                let signature = edge_signature(&total_edges);
+                if seen_signatures.contains(&signature) {
+                    continue;
+                }
+
+                candidates.push(KShortestCandidate {
+                    cost: root_cost + spur_cost,
+                    signature,
+                    path: (total_nodes, total_edges, root_cost + spur_cost),
+                });
+            }
+
+            // The same candidate can be generated from more than one spur
+            // position across iterations before either copy is selected;
+            // skip any that turned out to duplicate an already-chosen path.
+            let next = loop {
+                match candidates.pop() {
+                    Some(candidate) if seen_signatures.contains(&candidate.signature) => continue,
+                    Some(candidate) => break Some(candidate),
+                    None => break None,
+                }
+            };
+            let Some(KShortestCandidate { signature, path, .. }) = next else {
+                break;
+            };
+            seen_signatures.insert(signature);
+            found.push(path);
+        }
+
+        found
+    }
+
+    /// Find the non-dominated (Pareto-optimal) set of routes across cost,
+    /// carbon, transit time, and labor score, via label-setting
+    /// multi-criteria shortest path (Martins' algorithm): each node carries
+    /// its own frontier of non-dominated labels, and an edge relaxation is
+    /// only kept if no existing label at the neighbor already dominates it
+    /// (dominating labels prune any labels they beat in turn). Expansion
+    /// order is a normalized weighted-sum scalarization, which only bounds
+    /// how quickly the frontier is discovered — it does not affect which
+    /// labels ultimately survive as non-dominated.
+    pub fn pareto_paths(
+        &self,
+        from: &str,
+        to: &str,
+        weight_kg: f64,
+        constraints: &RouteConstraints,
+    ) -> Vec<ParetoRoute<'_>> {
+        let (Some(from_idx), Some(to_idx)) = (self.get_node_index(from), self.get_node_index(to))
+        else {
+            return vec![];
+        };
+
+        let mut arena: Vec<ParetoLabelEntry> = Vec::new();
+        let mut frontier: HashMap<NodeIndex, Vec<usize>> = HashMap::new();
+        let mut open: BinaryHeap<ScalarizedEntry> = BinaryHeap::new();
+
+        let root = ParetoLabel {
+            cost: Decimal::ZERO,
+            carbon_kg: 0.0,
+            transit_hours: 0.0,
+            labor_sum: 0.0,
+            hops: 0,
+        };
+        arena.push(ParetoLabelEntry {
+            label: root,
+            node: from_idx,
+            predecessor: None,
+            via_edge: None,
+        });
+        frontier.entry(from_idx).or_default().push(0);
+        open.push(ScalarizedEntry {
+            priority: root.scalarize(),
+            arena_idx: 0,
+        });
+
+        let mut settled_at_target: Vec<usize> = Vec::new();
+
+        while let Some(ScalarizedEntry { arena_idx, .. }) = open.pop() {
+            let ParetoLabelEntry { label: current_label, node: current_node, .. } = arena[arena_idx];
+
+            // This label may have since been pruned (dominated by a label
+            // discovered after it was pushed); skip it rather than expand.
+            if !frontier.get(&current_node).map_or(false, |v| v.contains(&arena_idx)) {
+                continue;
+            }
+
+            if current_node == to_idx {
+                settled_at_target.push(arena_idx);
+                continue;
+            }
+
+            for edge_ref in self.graph.edges(current_node) {
+                let edge = edge_ref.weight();
+                if !constraints.is_compliant(edge, Some(weight_kg)) {
+                    continue;
+                }
+                let neighbor = edge_ref.target();
+                let candidate = ParetoLabel {
+                    cost: current_label.cost + edge.calculate_cost(weight_kg),
+                    carbon_kg: current_label.carbon_kg + edge.calculate_carbon(weight_kg),
+                    transit_hours: current_label.transit_hours + edge.transit_hours,
+                    labor_sum: current_label.labor_sum + edge.labor_score(1500), // TODO: Get actual country min wage
+                    hops: current_label.hops + 1,
+                };
+
+                let neighbor_labels = frontier.entry(neighbor).or_default();
+                if neighbor_labels
+                    .iter()
+                    .any(|&idx| arena[idx].label.dominates(&candidate))
+                {
+                    continue;
+                }
+                neighbor_labels.retain(|&idx| !candidate.dominates(&arena[idx].label));
+
+                let new_idx = arena.len();
+                arena.push(ParetoLabelEntry {
+                    label: candidate,
+                    node: neighbor,
+                    predecessor: Some(arena_idx),
+                    via_edge: Some(edge_ref.id()),
+                });
+                frontier.get_mut(&neighbor).unwrap().push(new_idx);
+                open.push(ScalarizedEntry {
+                    priority: candidate.scalarize(),
+                    arena_idx: new_idx,
+                });
+            }
+        }
+
+        let mut routes = Vec::new();
+        'labels: for &idx in &settled_at_target {
+            let label = arena[idx].label;
+            for &other in &settled_at_target {
+                if other != idx && arena[other].label.dominates(&label) {
+                    continue 'labels;
+                }
+            }
+
+            let mut path = vec![to_idx];
+            let mut edges = Vec::new();
+            let mut cursor = idx;
+            while let Some(pred) = arena[cursor].predecessor {
+                path.push(arena[pred].node);
+                if let Some(edge_idx) = arena[cursor].via_edge {
+                    edges.push(&self.graph[edge_idx]);
+                }
+                cursor = pred;
+            }
+            path.reverse();
+            edges.reverse();
+
+            routes.push(ParetoRoute {
+                path,
+                edges,
+                total_cost_usd: label.cost,
+                total_carbon_kg: label.carbon_kg,
+                total_transit_hours: label.transit_hours,
+                labor_score: label.labor_avg(),
+            });
+        }
+
+        routes
+    }
+
+    /// Collapse cost, carbon, transit time, and labor score into a single
+    /// scalar via caller-supplied `weights` and find the best path under
+    /// plain Dijkstra — a single-objective convenience alternative to
+    /// `pareto_paths` for a caller who just wants "the" cheapest, greenest,
+    /// or fastest-but-ethical route rather than the whole frontier.
+    pub fn best_weighted_path(
+        &self,
+        from: &str,
+        to: &str,
+        weight_kg: f64,
+        weights: PathWeights,
+        constraints: &RouteConstraints,
+    ) -> Option<(Vec<NodeIndex>, Vec<&TransportEdge>, f64)> {
+        let from_idx = self.get_node_index(from)?;
+        let to_idx = self.get_node_index(to)?;
+
+        self.dijkstra_with_path(
+            from_idx,
+            to_idx,
+            |edge| {
+                let cost = edge
+                    .calculate_cost(weight_kg)
+                    .to_string()
+                    .parse::<f64>()
+                    .unwrap_or(0.0);
+                let carbon = edge.calculate_carbon(weight_kg);
+                let transit = edge.transit_hours;
+                let labor_penalty = 1.0 - edge.labor_score(1500); // TODO: Get actual country min wage
+
+                weights.cost_weight * cost
+                    + weights.carbon_weight * carbon
+                    + weights.time_weight * transit
+                    + weights.labor_weight * labor_penalty
+            },
+            |edge| constraints.is_compliant(edge, Some(weight_kg)),
+        )
+    }
+
+    /// Plain Dijkstra with full path reconstruction, generic over the edge
+    /// cost function. Shared by `best_weighted_path`; `shortest_path_astar`
+    /// has its own copy since it additionally threads a heuristic and a
+    /// `Decimal`-typed accumulated cost.
+    fn dijkstra_with_path<F, C>(
+        &self,
+        from_idx: NodeIndex,
+        to_idx: NodeIndex,
+        edge_cost: F,
+        is_compliant: C,
+    ) -> Option<(Vec<NodeIndex>, Vec<&TransportEdge>, f64)>
+    where
+        F: Fn(&TransportEdge) -> f64,
+        C: Fn(&TransportEdge) -> bool,
+    {
+        let mut dist: HashMap<NodeIndex, f64> = HashMap::new();
+        let mut predecessor: HashMap<NodeIndex, NodeIndex> = HashMap::new();
+        let mut open: BinaryHeap<AstarState> = BinaryHeap::new();
+
+        dist.insert(from_idx, 0.0);
+        open.push(AstarState { priority: 0.0, node: from_idx });
+
+        while let Some(AstarState { node: current, .. }) = open.pop() {
+            if current == to_idx {
+                let mut path = vec![current];
+                let mut cursor = current;
+                while let Some(&prev) = predecessor.get(&cursor) {
+                    path.push(prev);
+                    cursor = prev;
+                }
+                path.reverse();
+
+                let edges = path
+                    .windows(2)
+                    .filter_map(|pair| {
+                        self.graph
+                            .edges_connecting(pair[0], pair[1])
+                            .filter(|e| is_compliant(e.weight()))
+                            .min_by(|a, b| {
+                                edge_cost(a.weight())
+                                    .partial_cmp(&edge_cost(b.weight()))
+                                    .unwrap_or(Ordering::Equal)
+                            })
+                            .map(|e| e.weight())
+                    })
+                    .collect();
+
+                return Some((path, edges, dist[&to_idx]));
+            }
+
+            let current_dist = dist[&current];
+            for edge_ref in self.graph.edges(current) {
+                if !is_compliant(edge_ref.weight()) {
+                    continue;
+                }
+                let neighbor = edge_ref.target();
+                let tentative = current_dist + edge_cost(edge_ref.weight());
+
+                if dist.get(&neighbor).map_or(true, |&d| tentative < d) {
+                    dist.insert(neighbor, tentative);
+                    predecessor.insert(neighbor, current);
+                    open.push(AstarState { priority: tentative, node: neighbor });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Find the path that minimizes true elapsed time: transit hours, plus
+    /// each node's `avg_dwell_hours`, plus `TransportMode::mode_transfer_hours`
+    /// whenever the route switches mode. Plain per-node Dijkstra cannot
+    /// express this, since the transfer penalty depends on which mode the
+    /// path arrived on — so this runs a labeled Dijkstra over the expanded
+    /// `(node, incoming mode)` state space instead, with the origin state
+    /// carrying no incoming mode (and so no transfer penalty).
+    pub fn fastest_path(
+        &self,
+        from: &str,
+        to: &str,
+        constraints: &RouteConstraints,
+    ) -> Option<(Vec<FastestPathHop<'_>>, f64)> {
+        let from_idx = self.get_node_index(from)?;
+        let to_idx = self.get_node_index(to)?;
+
+        let origin = TimeState {
+            node: from_idx,
+            incoming_mode: None,
+        };
+
+        let mut dist: HashMap<TimeState, f64> = HashMap::new();
+        let mut predecessor: HashMap<TimeState, (TimeState, petgraph::graph::EdgeIndex)> =
+            HashMap::new();
+        let mut open: BinaryHeap<TimeEntry> = BinaryHeap::new();
+
+        dist.insert(origin, 0.0);
+        open.push(TimeEntry {
+            priority: 0.0,
+            state: origin,
+        });
+
+        while let Some(TimeEntry { state: current, .. }) = open.pop() {
+            if current.node == to_idx {
+                let mut hops = Vec::new();
+                let mut cursor = current;
+                while let Some(&(prev, edge_idx)) = predecessor.get(&cursor) {
+                    let edge = &self.graph[edge_idx];
+                    let mode_transfer = prev.incoming_mode.map_or(false, |m| m != edge.mode);
+                    hops.push(FastestPathHop {
+                        node: cursor.node,
+                        edge: Some(edge),
+                        mode_transfer,
+                    });
+                    cursor = prev;
+                }
+                // `cursor` is now the origin state, which has no predecessor.
+                hops.push(FastestPathHop {
+                    node: cursor.node,
+                    edge: None,
+                    mode_transfer: false,
+                });
+                hops.reverse();
+
+                return Some((hops, dist[&current]));
+            }
+
+            let current_dist = dist[&current];
+            for edge_ref in self.graph.edges(current.node) {
+                let edge = edge_ref.weight();
+                if !constraints.is_compliant(edge, None) {
+                    continue;
+                }
+                let neighbor_node = edge_ref.target();
+
+                let transfer_hours = match current.incoming_mode {
+                    Some(prev_mode) if prev_mode != edge.mode => {
+                        prev_mode.mode_transfer_hours(&edge.mode)
+                    }
+                    _ => 0.0,
+                };
+                let dwell_hours = self.graph[neighbor_node].avg_dwell_hours;
+                let tentative = current_dist + edge.transit_hours + transfer_hours + dwell_hours;
+
+                let next_state = TimeState {
+                    node: neighbor_node,
+                    incoming_mode: Some(edge.mode),
+                };
+                if dist.get(&next_state).map_or(true, |&d| tentative < d) {
+                    dist.insert(next_state, tentative);
+                    predecessor.insert(next_state, (current, edge_ref.id()));
+                    open.push(TimeEntry {
+                        priority: tentative,
+                        state: next_state,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
     /// Get all edges from a node
     pub fn edges_from(&self, code: &str) -> Vec<(&TransportNode, &TransportEdge)> {
         let Some(&idx) = self.node_index.get(code) else {
@@ -236,6 +1373,62 @@ impl TransportGraph {
     pub fn edges(&self) -> impl Iterator<Item = &TransportEdge> {
         self.graph.edge_weights()
     }
+
+    /// Cheap invariants a freshly loaded snapshot must satisfy before it is
+    /// allowed to replace the live graph: a non-empty node set, every edge
+    /// endpoint resolving to a real node, and no negative distances/costs.
+    pub fn validate(&self) -> Result<(), GraphValidationError> {
+        if self.node_count() == 0 {
+            return Err(GraphValidationError("graph has zero nodes".to_string()));
+        }
+
+        for edge_ref in self.graph.edge_references() {
+            if self.graph.node_weight(edge_ref.source()).is_none()
+                || self.graph.node_weight(edge_ref.target()).is_none()
+            {
+                return Err(GraphValidationError(format!(
+                    "edge {} has a dangling endpoint",
+                    edge_ref.weight().code
+                )));
+            }
+
+            let edge = edge_ref.weight();
+            if edge.distance_km < 0.0 {
+                return Err(GraphValidationError(format!(
+                    "edge {} has a negative distance_km ({})",
+                    edge.code, edge.distance_km
+                )));
+            }
+            if edge.base_cost_usd < Decimal::ZERO || edge.cost_per_kg < Decimal::ZERO {
+                return Err(GraphValidationError(format!(
+                    "edge {} has a negative cost",
+                    edge.code
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deterministic content hash over the sorted node/edge set, used to
+    /// recognize a repeatedly-failing snapshot (e.g. to blacklist it) without
+    /// re-running full validation every time it reappears.
+    pub fn content_hash(&self) -> u64 {
+        let mut node_codes: Vec<&str> = self.graph.node_weights().map(|n| n.code.as_str()).collect();
+        node_codes.sort_unstable();
+
+        let mut edge_keys: Vec<String> = self
+            .graph
+            .edge_weights()
+            .map(|e| format!("{}|{}|{}|{}", e.code, e.distance_km, e.base_cost_usd, e.cost_per_kg))
+            .collect();
+        edge_keys.sort_unstable();
+
+        let mut hasher = DefaultHasher::new();
+        node_codes.hash(&mut hasher);
+        edge_keys.hash(&mut hasher);
+        hasher.finish()
+    }
 }
 
 impl Default for TransportGraph {
@@ -299,4 +1492,689 @@ mod tests {
         assert_eq!(graph.node_count(), 2);
         assert_eq!(graph.edge_count(), 1);
     }
+
+    fn sample_node(code: &str) -> TransportNode {
+        TransportNode {
+            id: format!("node-{code}"),
+            code: code.to_string(),
+            name: code.to_string(),
+            country_code: "XX".to_string(),
+            lat: 0.0,
+            lon: 0.0,
+            modes: vec![TransportMode::Maritime],
+            avg_dwell_hours: 12.0,
+        }
+    }
+
+    fn sample_edge(code: &str) -> TransportEdge {
+        TransportEdge {
+            id: format!("edge-{code}"),
+            code: code.to_string(),
+            mode: TransportMode::Maritime,
+            carrier_code: "CARR".to_string(),
+            carrier_name: "Carrier".to_string(),
+            distance_km: 100.0,
+            base_cost_usd: Decimal::from(10),
+            cost_per_kg: Decimal::ZERO,
+            transit_hours: 5.0,
+            carbon_per_tonne_km: 0.01,
+            carrier_wage_cents: 2000,
+            carrier_safety_rating: 4,
+            carrier_unionized: true,
+            carrier_sanctioned: false,
+            active: true,
+        }
+    }
+
+    #[test]
+    fn test_upsert_node_replaces_in_place() {
+        let mut graph = TransportGraph::new();
+        let idx = graph.add_node(sample_node("AAA"));
+
+        let mut updated = sample_node("AAA");
+        updated.name = "Updated".to_string();
+        let upserted_idx = graph.upsert_node(updated);
+
+        assert_eq!(upserted_idx, idx);
+        assert_eq!(graph.node_count(), 1);
+        assert_eq!(graph.get_node("AAA").unwrap().name, "Updated");
+    }
+
+    #[test]
+    fn test_remove_node_keeps_index_consistent() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+        graph.add_node(sample_node("CCC"));
+
+        assert!(graph.remove_node("AAA"));
+        assert_eq!(graph.node_count(), 2);
+        assert!(graph.get_node("AAA").is_none());
+        assert!(graph.get_node("BBB").is_some());
+        assert!(graph.get_node("CCC").is_some());
+    }
+
+    #[test]
+    fn test_upsert_and_remove_edge_by_code() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+        assert!(graph.upsert_edge("AAA", "BBB", sample_edge("E1")));
+        assert_eq!(graph.edge_count(), 1);
+
+        let mut updated = sample_edge("E1");
+        updated.distance_km = 999.0;
+        assert!(graph.upsert_edge("AAA", "BBB", updated));
+        assert_eq!(graph.edge_count(), 1);
+        assert_eq!(graph.edges_from("AAA")[0].1.distance_km, 999.0);
+
+        assert!(graph.remove_edge_by_code("E1"));
+        assert_eq!(graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_graph() {
+        let graph = TransportGraph::new();
+        assert!(graph.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_negative_distance() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+
+        let mut bad_edge = sample_edge("E1");
+        bad_edge.distance_km = -10.0;
+        graph.add_edge("AAA", "BBB", bad_edge);
+
+        assert!(graph.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_accepts_healthy_graph() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+        graph.add_edge("AAA", "BBB", sample_edge("E1"));
+
+        assert!(graph.validate().is_ok());
+    }
+
+    #[test]
+    fn test_content_hash_is_order_independent_but_content_sensitive() {
+        let mut graph_a = TransportGraph::new();
+        graph_a.add_node(sample_node("AAA"));
+        graph_a.add_node(sample_node("BBB"));
+        graph_a.add_edge("AAA", "BBB", sample_edge("E1"));
+
+        let mut graph_b = TransportGraph::new();
+        graph_b.add_node(sample_node("BBB"));
+        graph_b.add_node(sample_node("AAA"));
+        graph_b.add_edge("AAA", "BBB", sample_edge("E1"));
+
+        assert_eq!(graph_a.content_hash(), graph_b.content_hash());
+
+        let mut updated_edge = sample_edge("E1");
+        updated_edge.distance_km = 999.0;
+        graph_b.upsert_edge("AAA", "BBB", updated_edge);
+
+        assert_ne!(graph_a.content_hash(), graph_b.content_hash());
+    }
+
+    #[test]
+    fn test_shortest_path_astar_reconstructs_real_path() {
+        let mut graph = TransportGraph::new();
+
+        let mut shanghai = sample_node("CNSHA");
+        shanghai.lat = 31.2304;
+        shanghai.lon = 121.4737;
+        let mut singapore = sample_node("SGSIN");
+        singapore.lat = 1.3521;
+        singapore.lon = 103.8198;
+        let mut rotterdam = sample_node("NLRTM");
+        rotterdam.lat = 51.9225;
+        rotterdam.lon = 4.4792;
+
+        graph.add_node(shanghai);
+        graph.add_node(singapore);
+        graph.add_node(rotterdam);
+
+        // Direct route is cheaper than via Singapore, so A* should skip it.
+        let mut direct = sample_edge("CNSHA-NLRTM");
+        direct.distance_km = 19500.0;
+        direct.base_cost_usd = Decimal::from(4000);
+        graph.add_edge("CNSHA", "NLRTM", direct);
+
+        let mut leg1 = sample_edge("CNSHA-SGSIN");
+        leg1.distance_km = 4500.0;
+        leg1.base_cost_usd = Decimal::from(4000);
+        graph.add_edge("CNSHA", "SGSIN", leg1);
+
+        let mut leg2 = sample_edge("SGSIN-NLRTM");
+        leg2.distance_km = 15500.0;
+        leg2.base_cost_usd = Decimal::from(4000);
+        graph.add_edge("SGSIN", "NLRTM", leg2);
+
+        let (path, edges, cost) = graph.shortest_path_astar("CNSHA", "NLRTM", 1000.0, &RouteConstraints::default()).unwrap();
+
+        let codes: Vec<&str> = path.iter().map(|&idx| graph.inner()[idx].code.as_str()).collect();
+        assert_eq!(codes, vec!["CNSHA", "NLRTM"]);
+        assert_eq!(edges.len(), 1);
+        assert_eq!(edges[0].code, "CNSHA-NLRTM");
+        assert_eq!(cost, Decimal::from(4000));
+    }
+
+    #[test]
+    fn test_shortest_path_astar_multi_hop() {
+        let mut graph = TransportGraph::new();
+
+        let mut a = sample_node("AAA");
+        a.lat = 10.0;
+        a.lon = 0.0;
+        let mut b = sample_node("BBB");
+        b.lat = 20.0;
+        b.lon = 10.0;
+        let mut c = sample_node("CCC");
+        c.lat = 30.0;
+        c.lon = 20.0;
+
+        graph.add_node(a);
+        graph.add_node(b);
+        graph.add_node(c);
+
+        graph.add_edge("AAA", "BBB", sample_edge("AB"));
+        graph.add_edge("BBB", "CCC", sample_edge("BC"));
+
+        let (path, edges, cost) = graph.shortest_path_astar("AAA", "CCC", 1000.0, &RouteConstraints::default()).unwrap();
+
+        let codes: Vec<&str> = path.iter().map(|&idx| graph.inner()[idx].code.as_str()).collect();
+        assert_eq!(codes, vec!["AAA", "BBB", "CCC"]);
+        assert_eq!(edges.iter().map(|e| e.code.as_str()).collect::<Vec<_>>(), vec!["AB", "BC"]);
+        assert_eq!(cost, sample_edge("AB").base_cost_usd + sample_edge("BC").base_cost_usd);
+    }
+
+    #[test]
+    fn test_shortest_path_astar_falls_back_to_none_without_coordinates() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA")); // lat/lon both 0.0
+        graph.add_node(sample_node("BBB"));
+        graph.add_edge("AAA", "BBB", sample_edge("E1"));
+
+        assert!(graph.shortest_path_astar("AAA", "BBB", 1000.0, &RouteConstraints::default()).is_none());
+        // The caller's fallback still finds a cost via plain Dijkstra.
+        assert!(graph.shortest_path_by_cost("AAA", "BBB", 1000.0, &RouteConstraints::default()).is_some());
+    }
+
+    #[test]
+    fn test_haversine_km_known_distance() {
+        // Rotterdam to Shanghai is roughly 8900km great-circle.
+        let dist = haversine_km(51.9225, 4.4792, 31.2304, 121.4737);
+        assert!((dist - 8900.0).abs() < 300.0, "unexpected haversine distance: {dist}");
+    }
+
+    /// Two routes A->B where one is cheaper/dirtier and the other is
+    /// pricier/cleaner: neither dominates, so both should survive.
+    fn pareto_test_graph() -> TransportGraph {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+
+        let mut cheap_dirty = sample_edge("CHEAP");
+        cheap_dirty.base_cost_usd = Decimal::from(100);
+        cheap_dirty.distance_km = 1000.0;
+        cheap_dirty.carbon_per_tonne_km = 0.1;
+        cheap_dirty.transit_hours = 10.0;
+        graph.add_edge("AAA", "BBB", cheap_dirty);
+
+        let mut pricey_clean = sample_edge("CLEAN");
+        pricey_clean.base_cost_usd = Decimal::from(500);
+        pricey_clean.distance_km = 1000.0;
+        pricey_clean.carbon_per_tonne_km = 0.01;
+        pricey_clean.transit_hours = 20.0;
+        graph.add_edge("AAA", "BBB", pricey_clean);
+
+        graph
+    }
+
+    #[test]
+    fn test_pareto_paths_keeps_non_dominated_routes() {
+        let graph = pareto_test_graph();
+        let routes = graph.pareto_paths("AAA", "BBB", 1000.0, &RouteConstraints::default());
+
+        assert_eq!(routes.len(), 2, "neither route dominates the other");
+        let codes: std::collections::HashSet<&str> = routes
+            .iter()
+            .flat_map(|r| r.edges.iter().map(|e| e.code.as_str()))
+            .collect();
+        assert!(codes.contains("CHEAP"));
+        assert!(codes.contains("CLEAN"));
+    }
+
+    #[test]
+    fn test_pareto_paths_prunes_dominated_route() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+
+        // Strictly worse on every objective than "GOOD" below.
+        let mut dominated = sample_edge("BAD");
+        dominated.base_cost_usd = Decimal::from(500);
+        dominated.distance_km = 1000.0;
+        dominated.carbon_per_tonne_km = 0.1;
+        dominated.transit_hours = 20.0;
+        graph.add_edge("AAA", "BBB", dominated);
+
+        let mut dominant = sample_edge("GOOD");
+        dominant.base_cost_usd = Decimal::from(100);
+        dominant.distance_km = 1000.0;
+        dominant.carbon_per_tonne_km = 0.01;
+        dominant.transit_hours = 10.0;
+        graph.add_edge("AAA", "BBB", dominant);
+
+        let routes = graph.pareto_paths("AAA", "BBB", 1000.0, &RouteConstraints::default());
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].edges[0].code, "GOOD");
+    }
+
+    #[test]
+    fn test_best_weighted_path_favors_cheap_when_cost_weighted() {
+        let graph = pareto_test_graph();
+        let weights = PathWeights {
+            cost_weight: 1.0,
+            time_weight: 0.0,
+            carbon_weight: 0.0,
+            labor_weight: 0.0,
+        };
+
+        let (_, edges, _) = graph.best_weighted_path("AAA", "BBB", 1000.0, weights, &RouteConstraints::default()).unwrap();
+        assert_eq!(edges[0].code, "CHEAP");
+    }
+
+    #[test]
+    fn test_best_weighted_path_favors_clean_when_carbon_weighted() {
+        let graph = pareto_test_graph();
+        let weights = PathWeights {
+            cost_weight: 0.0,
+            time_weight: 0.0,
+            carbon_weight: 1.0,
+            labor_weight: 0.0,
+        };
+
+        let (_, edges, _) = graph.best_weighted_path("AAA", "BBB", 1000.0, weights, &RouteConstraints::default()).unwrap();
+        assert_eq!(edges[0].code, "CLEAN");
+    }
+
+    #[test]
+    fn test_fastest_path_adds_dwell_and_no_transfer_when_mode_stays_same() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        let mut bbb = sample_node("BBB");
+        bbb.avg_dwell_hours = 3.0;
+        graph.add_node(bbb);
+
+        let mut edge = sample_edge("E1");
+        edge.mode = TransportMode::Maritime;
+        edge.transit_hours = 10.0;
+        graph.add_edge("AAA", "BBB", edge);
+
+        let (hops, total_hours) = graph.fastest_path("AAA", "BBB", &RouteConstraints::default()).unwrap();
+        assert_eq!(hops.len(), 2);
+        assert!(hops[0].edge.is_none());
+        assert!(!hops[1].mode_transfer);
+        // No prior mode at the origin, so only transit + dwell, no transfer penalty.
+        assert_eq!(total_hours, 13.0);
+    }
+
+    #[test]
+    fn test_fastest_path_pays_mode_transfer_penalty() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+        graph.add_node(sample_node("CCC"));
+
+        let mut leg1 = sample_edge("AB");
+        leg1.mode = TransportMode::Maritime;
+        leg1.transit_hours = 10.0;
+        graph.add_edge("AAA", "BBB", leg1);
+
+        let mut leg2 = sample_edge("BC");
+        leg2.mode = TransportMode::Rail;
+        leg2.transit_hours = 5.0;
+        graph.add_edge("BBB", "CCC", leg2);
+
+        let (hops, total_hours) = graph.fastest_path("AAA", "CCC", &RouteConstraints::default()).unwrap();
+        assert_eq!(hops.len(), 3);
+        assert!(!hops[1].mode_transfer); // arriving at BBB, nothing to transfer from yet
+        assert!(hops[2].mode_transfer); // Maritime -> Rail at BBB
+
+        let expected = 10.0 // leg1 transit
+            + 12.0 // dwell at BBB (sample_node default avg_dwell_hours)
+            + TransportMode::Maritime.mode_transfer_hours(&TransportMode::Rail)
+            + 5.0 // leg2 transit
+            + 12.0; // dwell at CCC
+        assert_eq!(total_hours, expected);
+    }
+
+    #[test]
+    fn test_fastest_path_prefers_fewer_transfers_over_shorter_edge_sum() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+        graph.add_node(sample_node("CCC"));
+
+        // Direct: one long maritime leg, no transfer.
+        let mut direct = sample_edge("DIRECT");
+        direct.mode = TransportMode::Maritime;
+        direct.transit_hours = 30.0;
+        graph.add_edge("AAA", "CCC", direct);
+
+        // Via BBB: shorter edge-sum but pays a maritime->rail transfer (24h).
+        let mut leg1 = sample_edge("AB");
+        leg1.mode = TransportMode::Maritime;
+        leg1.transit_hours = 5.0;
+        graph.add_edge("AAA", "BBB", leg1);
+
+        let mut leg2 = sample_edge("BC");
+        leg2.mode = TransportMode::Rail;
+        leg2.transit_hours = 5.0;
+        graph.add_edge("BBB", "CCC", leg2);
+
+        let (hops, _) = graph.fastest_path("AAA", "CCC", &RouteConstraints::default()).unwrap();
+        assert_eq!(hops.len(), 2, "the direct route should win once the transfer penalty is paid");
+        assert_eq!(hops[1].edge.unwrap().code, "DIRECT");
+    }
+
+    fn geo_node(code: &str, lat: f64, lon: f64, modes: Vec<TransportMode>) -> TransportNode {
+        TransportNode {
+            id: format!("node-{code}"),
+            code: code.to_string(),
+            name: code.to_string(),
+            country_code: "XX".to_string(),
+            lat,
+            lon,
+            modes,
+            avg_dwell_hours: 12.0,
+        }
+    }
+
+    fn geo_test_graph() -> TransportGraph {
+        let mut graph = TransportGraph::new();
+        graph.add_node(geo_node(
+            "CNSHA",
+            31.2304,
+            121.4737,
+            vec![TransportMode::Maritime],
+        ));
+        graph.add_node(geo_node(
+            "NLRTM",
+            51.9225,
+            4.4792,
+            vec![TransportMode::Maritime, TransportMode::Rail],
+        ));
+        graph.add_node(geo_node(
+            "DEHAM",
+            53.5511,
+            9.9937,
+            vec![TransportMode::Rail, TransportMode::Road],
+        ));
+        graph
+    }
+
+    #[test]
+    fn test_nearest_node_returns_closest_by_geography() {
+        let graph = geo_test_graph();
+
+        // A point close to Hamburg but also nearer to Rotterdam than to
+        // Shanghai — should resolve to Hamburg, the true closest.
+        let nearest = graph.nearest_node(53.0, 10.0, &[]).unwrap();
+        assert_eq!(nearest.code, "DEHAM");
+    }
+
+    #[test]
+    fn test_nearest_node_applies_mode_filter() {
+        let graph = geo_test_graph();
+
+        // Nearest overall is Hamburg, but it has no Maritime service; the
+        // nearest *maritime-capable* node should skip past it to Rotterdam.
+        let nearest = graph
+            .nearest_node(53.0, 10.0, &[TransportMode::Maritime])
+            .unwrap();
+        assert_eq!(nearest.code, "NLRTM");
+    }
+
+    #[test]
+    fn test_nearest_node_empty_graph_returns_none() {
+        let graph = TransportGraph::new();
+        assert!(graph.nearest_node(0.0, 0.0, &[]).is_none());
+    }
+
+    #[test]
+    fn test_nodes_within_km_finds_nearby_and_excludes_far() {
+        let graph = geo_test_graph();
+
+        // Rotterdam and Hamburg are ~410km apart great-circle; Shanghai is many
+        // thousand km away from both.
+        let nearby = graph.nodes_within_km(51.9225, 4.4792, 500.0);
+        let codes: Vec<&str> = nearby.iter().map(|n| n.code.as_str()).collect();
+        assert!(codes.contains(&"NLRTM"));
+        assert!(codes.contains(&"DEHAM"));
+        assert!(!codes.contains(&"CNSHA"));
+    }
+
+    #[test]
+    fn test_nodes_within_km_rebuilds_after_remove_node() {
+        let mut graph = geo_test_graph();
+        graph.remove_node("NLRTM");
+
+        let nearby = graph.nodes_within_km(51.9225, 4.4792, 500.0);
+        assert!(
+            nearby.iter().all(|n| n.code != "NLRTM"),
+            "removed node must not linger in the spatial index"
+        );
+    }
+
+    #[test]
+    fn test_bulk_load_nodes_matches_sequential_add_node() {
+        let mut graph = TransportGraph::new();
+        graph.bulk_load_nodes(vec![
+            geo_node("NLRTM", 51.9225, 4.4792, vec![TransportMode::Maritime]),
+            geo_node("DEHAM", 53.5511, 9.9937, vec![TransportMode::Maritime]),
+        ]);
+
+        assert_eq!(graph.node_count(), 2);
+        assert!(graph.get_node("NLRTM").is_some());
+        assert!(graph.get_node("DEHAM").is_some());
+
+        // The spatial index must reflect the bulk-loaded nodes, not just
+        // `node_index` — a rebuild skipped or done against a stale node
+        // set would leave `nearest_node` blind to them.
+        let nearest = graph.nearest_node(53.0, 10.0, &[]).unwrap();
+        assert_eq!(nearest.code, "DEHAM");
+    }
+
+    /// A diamond graph with three distinct AAA->DDD routes of increasing
+    /// cost: via BBB (cheapest), via CCC (middle), and direct (priciest).
+    fn k_shortest_test_graph() -> TransportGraph {
+        let mut graph = TransportGraph::new();
+        graph.add_node(geo_node("AAA", 10.0, 10.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("BBB", 20.0, 20.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("CCC", 20.0, 30.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("DDD", 30.0, 40.0, vec![TransportMode::Maritime]));
+
+        let mut ab = sample_edge("AB");
+        ab.base_cost_usd = Decimal::from(10);
+        graph.add_edge("AAA", "BBB", ab);
+
+        let mut bd = sample_edge("BD");
+        bd.base_cost_usd = Decimal::from(10);
+        graph.add_edge("BBB", "DDD", bd);
+
+        let mut ac = sample_edge("AC");
+        ac.base_cost_usd = Decimal::from(30);
+        graph.add_edge("AAA", "CCC", ac);
+
+        let mut cd = sample_edge("CD");
+        cd.base_cost_usd = Decimal::from(30);
+        graph.add_edge("CCC", "DDD", cd);
+
+        let mut direct = sample_edge("DIRECT");
+        direct.base_cost_usd = Decimal::from(1000);
+        graph.add_edge("AAA", "DDD", direct);
+
+        graph
+    }
+
+    #[test]
+    fn test_k_shortest_paths_orders_by_increasing_cost() {
+        let graph = k_shortest_test_graph();
+
+        let paths = graph.k_shortest_paths("AAA", "DDD", 100.0, 3, &RouteConstraints::default());
+        assert_eq!(paths.len(), 3);
+
+        let costs: Vec<Decimal> = paths.iter().map(|(_, _, cost)| *cost).collect();
+        assert_eq!(costs[0], Decimal::from(20), "via BBB is cheapest");
+        assert_eq!(costs[1], Decimal::from(60), "via CCC is next");
+        assert_eq!(costs[2], Decimal::from(1000), "direct is the priciest");
+        assert!(costs.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_k_shortest_paths_are_distinct() {
+        let graph = k_shortest_test_graph();
+
+        let paths = graph.k_shortest_paths("AAA", "DDD", 100.0, 3, &RouteConstraints::default());
+        let signatures: HashSet<Vec<String>> = paths
+            .iter()
+            .map(|(_, edges, _)| edge_signature(edges))
+            .collect();
+        assert_eq!(signatures.len(), paths.len(), "no two returned paths should share an edge sequence");
+    }
+
+    #[test]
+    fn test_k_shortest_paths_caps_at_available_distinct_routes() {
+        let graph = k_shortest_test_graph();
+
+        // Only 3 distinct AAA->DDD routes exist in this fixture.
+        let paths = graph.k_shortest_paths("AAA", "DDD", 100.0, 10, &RouteConstraints::default());
+        assert_eq!(paths.len(), 3);
+    }
+
+    #[test]
+    fn test_k_shortest_paths_unreachable_returns_empty() {
+        let graph = k_shortest_test_graph();
+        graph.get_node("DDD"); // sanity: node exists, but no edge reaches it from nowhere
+        let paths = graph.k_shortest_paths("DDD", "AAA", 100.0, 3, &RouteConstraints::default());
+        assert!(paths.is_empty(), "the diamond's edges are one-directional");
+    }
+
+    #[test]
+    fn test_route_constraints_excludes_sanctioned_carrier() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(geo_node("AAA", 10.0, 10.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("BBB", 20.0, 20.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("CCC", 30.0, 30.0, vec![TransportMode::Maritime]));
+
+        // Direct route is cheap but sanctioned; the detour via BBB is
+        // compliant but costlier.
+        let mut direct = sample_edge("DIRECT");
+        direct.base_cost_usd = Decimal::from(5);
+        direct.carrier_sanctioned = true;
+        graph.add_edge("AAA", "CCC", direct);
+
+        let mut ab = sample_edge("AB");
+        ab.base_cost_usd = Decimal::from(10);
+        graph.add_edge("AAA", "BBB", ab);
+        let mut bc = sample_edge("BC");
+        bc.base_cost_usd = Decimal::from(10);
+        graph.add_edge("BBB", "CCC", bc);
+
+        let unconstrained = graph
+            .shortest_path_astar("AAA", "CCC", 100.0, &RouteConstraints::default())
+            .unwrap();
+        assert_eq!(unconstrained.2, Decimal::from(5), "the sanctioned direct edge is cheapest when unconstrained");
+
+        let constraints = RouteConstraints {
+            exclude_sanctioned: true,
+            ..Default::default()
+        };
+        let constrained = graph
+            .shortest_path_astar("AAA", "CCC", 100.0, &constraints)
+            .unwrap();
+        assert_eq!(constrained.2, Decimal::from(20), "must route around the sanctioned edge");
+        assert!(constrained.1.iter().all(|e| !e.carrier_sanctioned));
+    }
+
+    #[test]
+    fn test_route_constraints_min_safety_rating_filters_edges() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(geo_node("AAA", 10.0, 10.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("BBB", 20.0, 20.0, vec![TransportMode::Maritime]));
+
+        let mut unsafe_edge = sample_edge("UNSAFE");
+        unsafe_edge.carrier_safety_rating = 1;
+        graph.add_edge("AAA", "BBB", unsafe_edge);
+
+        let constraints = RouteConstraints {
+            min_safety_rating: Some(3),
+            ..Default::default()
+        };
+        assert!(graph
+            .shortest_path_astar("AAA", "BBB", 100.0, &constraints)
+            .is_none());
+    }
+
+    #[test]
+    fn test_shortest_path_compliant_distinguishes_no_route_from_no_compliant_route() {
+        let mut graph = TransportGraph::new();
+        graph.add_node(geo_node("AAA", 10.0, 10.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("BBB", 20.0, 20.0, vec![TransportMode::Maritime]));
+        graph.add_node(geo_node("CCC", 30.0, 30.0, vec![TransportMode::Maritime]));
+
+        let mut sanctioned = sample_edge("SANCTIONED");
+        sanctioned.carrier_sanctioned = true;
+        graph.add_edge("AAA", "BBB", sanctioned);
+
+        let constraints = RouteConstraints {
+            exclude_sanctioned: true,
+            ..Default::default()
+        };
+
+        // A route exists (AAA->BBB) but only via a forbidden edge.
+        assert!(matches!(
+            graph.shortest_path_compliant("AAA", "BBB", 100.0, &constraints),
+            Err(RouteSearchError::NoCompliantPath)
+        ));
+
+        // No route exists at all between AAA and the disconnected CCC.
+        assert!(matches!(
+            graph.shortest_path_compliant("AAA", "CCC", 100.0, &constraints),
+            Err(RouteSearchError::NoPathExists)
+        ));
+    }
+
+    #[test]
+    fn test_shortest_path_compliant_classifies_correctly_without_coordinates() {
+        // AAA/BBB both have lat/lon 0.0, so `shortest_path_astar` can't be
+        // used for classification (it always returns `None` for them); the
+        // classification step must fall back to `shortest_path_by_cost`
+        // instead of misreporting this as `NoPathExists`.
+        let mut graph = TransportGraph::new();
+        graph.add_node(sample_node("AAA"));
+        graph.add_node(sample_node("BBB"));
+
+        let mut sanctioned = sample_edge("SANCTIONED");
+        sanctioned.carrier_sanctioned = true;
+        graph.add_edge("AAA", "BBB", sanctioned);
+
+        let constraints = RouteConstraints {
+            exclude_sanctioned: true,
+            ..Default::default()
+        };
+
+        assert!(matches!(
+            graph.shortest_path_compliant("AAA", "BBB", 100.0, &constraints),
+            Err(RouteSearchError::NoCompliantPath)
+        ));
+    }
 }
@@ -0,0 +1,260 @@
+//! Graph Source
+//!
+//! Decouples the optimizer from a specific backing database, mirroring how
+//! a storage engine abstracts over swappable backends. `TransportGraph` can
+//! be built from a live SurrealDB instance, a local on-disk snapshot, or an
+//! in-memory fixture, all behind the same `GraphSource` trait.
+
+use crate::db::SurrealConnection;
+use crate::graph::{TransportEdge, TransportGraph, TransportNode};
+use crate::Config;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tonic::async_trait;
+
+/// Builds a `TransportGraph` from some backing source.
+#[async_trait]
+pub trait GraphSource: Send + Sync {
+    async fn load(&self, config: &Config) -> Result<TransportGraph>;
+}
+
+/// Loads the graph from the live SurrealDB instance, reusing the shared,
+/// lazily-established connection held in `AppState`.
+pub struct SurrealDbGraphSource {
+    conn: Arc<SurrealConnection>,
+}
+
+impl SurrealDbGraphSource {
+    pub fn new(conn: Arc<SurrealConnection>) -> Self {
+        SurrealDbGraphSource { conn }
+    }
+}
+
+#[async_trait]
+impl GraphSource for SurrealDbGraphSource {
+    async fn load(&self, config: &Config) -> Result<TransportGraph> {
+        crate::db::load_graph_from_surrealdb(&self.conn, config).await
+    }
+}
+
+/// Newline-delimited-JSON or single-JSON-document snapshot format used by
+/// `FileGraphSource`. Each line (or the single document) looks like:
+/// `{"nodes": [...], "edges": [{"from": "...", "to": "...", "edge": {...}}]}`
+#[derive(Debug, Deserialize)]
+struct GraphSnapshot {
+    nodes: Vec<TransportNode>,
+    edges: Vec<SnapshotEdge>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SnapshotEdge {
+    from: String,
+    to: String,
+    edge: TransportEdge,
+}
+
+/// Loads the graph from a local JSON snapshot file, used for local
+/// development and CI where a live SurrealDB is not available.
+pub struct FileGraphSource {
+    path: PathBuf,
+}
+
+impl FileGraphSource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        FileGraphSource { path: path.into() }
+    }
+
+    fn build_from_snapshot(snapshot: GraphSnapshot) -> TransportGraph {
+        let mut graph = TransportGraph::new();
+        graph.bulk_load_nodes(snapshot.nodes);
+        for SnapshotEdge { from, to, edge } in snapshot.edges {
+            graph.add_edge(&from, &to, edge);
+        }
+        graph
+    }
+}
+
+#[async_trait]
+impl GraphSource for FileGraphSource {
+    async fn load(&self, _config: &Config) -> Result<TransportGraph> {
+        let start = std::time::Instant::now();
+
+        let data = tokio::fs::read_to_string(&self.path)
+            .await
+            .with_context(|| format!("Failed to read graph snapshot at {:?}", self.path))?;
+
+        // A single JSON document, or else one `GraphSnapshot` per line whose
+        // nodes and edges are merged.
+        let snapshot: GraphSnapshot = match serde_json::from_str(&data) {
+            Ok(snapshot) => snapshot,
+            Err(_) => {
+                let mut merged = GraphSnapshot { nodes: Vec::new(), edges: Vec::new() };
+                for line in data.lines().filter(|l| !l.trim().is_empty()) {
+                    let part: GraphSnapshot = serde_json::from_str(line).with_context(|| {
+                        format!("Failed to parse graph snapshot at {:?}", self.path)
+                    })?;
+                    merged.nodes.extend(part.nodes);
+                    merged.edges.extend(part.edges);
+                }
+                merged
+            }
+        };
+
+        let mut graph = Self::build_from_snapshot(snapshot);
+        graph.load_time_ms = start.elapsed().as_millis() as u64;
+        graph.loaded_at = chrono::Utc::now();
+
+        Ok(graph)
+    }
+}
+
+/// Fixed in-memory graph, for unit and integration tests that need a
+/// `GraphSource` without touching the filesystem or network.
+pub struct FixtureGraphSource {
+    nodes: Vec<TransportNode>,
+    edges: Vec<SnapshotEdge>,
+}
+
+impl FixtureGraphSource {
+    pub fn new(nodes: Vec<TransportNode>, edges: Vec<(String, String, TransportEdge)>) -> Self {
+        FixtureGraphSource {
+            nodes,
+            edges: edges
+                .into_iter()
+                .map(|(from, to, edge)| SnapshotEdge { from, to, edge })
+                .collect(),
+        }
+    }
+}
+
+#[async_trait]
+impl GraphSource for FixtureGraphSource {
+    async fn load(&self, _config: &Config) -> Result<TransportGraph> {
+        let mut graph = TransportGraph::new();
+        graph.bulk_load_nodes(self.nodes.iter().cloned());
+        for SnapshotEdge { from, to, edge } in &self.edges {
+            graph.add_edge(from, to, edge.clone());
+        }
+        graph.loaded_at = chrono::Utc::now();
+        Ok(graph)
+    }
+}
+
+/// Wraps any two `GraphSource`s so a live-DB outage (the common case this
+/// guards against — `reload_graph`'s periodic `graph_reload_interval_secs`
+/// poll runs unattended) degrades to the last-good on-disk snapshot instead
+/// of leaving the optimizer running on a stale in-memory graph forever.
+/// The actual try/fall-back sequencing lives in `crate::fallback`, shared
+/// with `constraint_source::FallbackRuleSource`.
+pub struct FallbackGraphSource<P: GraphSource, F: GraphSource> {
+    primary: P,
+    fallback: F,
+}
+
+impl<P: GraphSource, F: GraphSource> FallbackGraphSource<P, F> {
+    pub fn new(primary: P, fallback: F) -> Self {
+        FallbackGraphSource { primary, fallback }
+    }
+}
+
+#[async_trait]
+impl<P: GraphSource, F: GraphSource> GraphSource for FallbackGraphSource<P, F> {
+    async fn load(&self, config: &Config) -> Result<TransportGraph> {
+        crate::fallback::try_with_fallback("graph source", self.primary.load(config), || {
+            self.fallback.load(config)
+        })
+        .await
+    }
+}
+
+/// Build the configured `GraphSource` chain: SurrealDB with a fallback to
+/// the last-good on-disk snapshot if the DB is unreachable.
+pub fn default_source(
+    config: &Config,
+    surreal: Arc<SurrealConnection>,
+) -> FallbackGraphSource<SurrealDbGraphSource, FileGraphSource> {
+    FallbackGraphSource::new(
+        SurrealDbGraphSource::new(surreal),
+        FileGraphSource::new(Path::new(&config.graph_snapshot_path)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::TransportMode;
+
+    fn test_config() -> Config {
+        Config {
+            grpc_port: 50051,
+            metrics_port: 8090,
+            surrealdb_url: String::new(),
+            surrealdb_user: String::new(),
+            surrealdb_pass: String::new(),
+            dragonfly_url: String::new(),
+            dragonfly_pass: None,
+            graph_reload_interval_secs: 300,
+            batch_max_concurrency: 8,
+            graph_snapshot_path: "does-not-exist.json".to_string(),
+            graph_live_updates: false,
+            constraint_rules_path: "does-not-exist.json".to_string(),
+            constraint_rules_reload_interval_secs: 300,
+            graph_snapshot_hash_blacklist: std::collections::HashSet::new(),
+            attestation_enabled: false,
+            attestation_key_id: "veds-default".to_string(),
+            attestation_local_seed: None,
+            attestation_remote_signer_url: None,
+            attestation_public_key: None,
+            precomputed_hub_codes: Vec::new(),
+            precomputed_cache_path: "does-not-exist.json".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixture_source_builds_graph() {
+        let node = TransportNode {
+            id: "node-1".to_string(),
+            code: "CNSHA".to_string(),
+            name: "Shanghai".to_string(),
+            country_code: "CN".to_string(),
+            lat: 31.2304,
+            lon: 121.4737,
+            modes: vec![TransportMode::Maritime],
+            avg_dwell_hours: 24.0,
+        };
+        let source = FixtureGraphSource::new(vec![node], vec![]);
+
+        let graph = source.load(&test_config()).await.unwrap();
+        assert_eq!(graph.node_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_source_uses_fallback_on_primary_error() {
+        struct AlwaysFails;
+
+        #[async_trait]
+        impl GraphSource for AlwaysFails {
+            async fn load(&self, _config: &Config) -> Result<TransportGraph> {
+                anyhow::bail!("primary unavailable")
+            }
+        }
+
+        let node = TransportNode {
+            id: "node-1".to_string(),
+            code: "NLRTM".to_string(),
+            name: "Rotterdam".to_string(),
+            country_code: "NL".to_string(),
+            lat: 51.9225,
+            lon: 4.4792,
+            modes: vec![TransportMode::Maritime],
+            avg_dwell_hours: 18.0,
+        };
+        let fallback = FixtureGraphSource::new(vec![node], vec![]);
+        let source = FallbackGraphSource::new(AlwaysFails, fallback);
+
+        let graph = source.load(&test_config()).await.unwrap();
+        assert_eq!(graph.node_count(), 1);
+    }
+}
@@ -2,11 +2,14 @@
 //!
 //! Exposes the optimizer via gRPC for integration with the Elixir API.
 
-use crate::{AppState, graph::TransportMode, optimizer::{Optimizer, OptimizeRequest as OptimizerRequest, CandidateRoute}, constraints::ConstraintEngine};
+use crate::{AppState, graph::{TransportMode, TransportGraph, TransportEdge, RouteConstraints}, optimizer::{Optimizer, OptimizeRequest as OptimizerRequest, CandidateRoute}, constraints::ConstraintEngine};
+use petgraph::graph::NodeIndex;
+use petgraph::visit::EdgeRef;
 use std::sync::Arc;
 use tonic::{Request, Response, Status};
 use chrono::DateTime;
 use rust_decimal::Decimal;
+use rayon::prelude::*;
 
 // Include generated protobuf code
 pub mod proto {
@@ -39,6 +42,11 @@ impl OptimizerService for OptimizerServiceImpl {
         let internal_request = match parse_optimize_request(&req) {
             Ok(r) => r,
             Err(e) => {
+                self.state
+                    .metrics
+                    .optimize_requests
+                    .with_label_values(&["failure"])
+                    .inc();
                 return Ok(Response::new(OptimizeResponse {
                     success: false,
                     error_message: e.to_string(),
@@ -53,18 +61,48 @@ impl OptimizerService for OptimizerServiceImpl {
         let graph = self.state.graph.read().await;
 
         // Create optimizer with constraint engine
-        let constraint_engine = ConstraintEngine::new(); // TODO: Load cache from Dragonfly
-        let optimizer = Optimizer::new(constraint_engine);
+        let constraint_engine = ConstraintEngine::with_cache(self.state.constraint_cache.read().await.clone());
+        let optimizer = Optimizer::with_precomputed_router(constraint_engine, Arc::clone(&self.state.precomputed_router));
 
         // Run optimization
         let result = optimizer.optimize(&graph, &internal_request);
 
-        // Convert to response
-        let routes: Vec<Route> = result
-            .routes
-            .into_iter()
-            .map(route_to_proto)
-            .collect();
+        self.state
+            .metrics
+            .optimize_requests
+            .with_label_values(&["success"])
+            .inc();
+        self.state
+            .metrics
+            .optimization_time_ms
+            .observe(result.optimization_time_ms as f64);
+        self.state
+            .metrics
+            .candidates_evaluated
+            .inc_by(result.candidates_evaluated as u64);
+
+        // Convert to response, signing each route's decision when
+        // attestation is configured so the response is tamper-evident.
+        let mut routes: Vec<Route> = Vec::with_capacity(result.routes.len());
+        for candidate in result.routes {
+            let attestation = match &self.state.signer {
+                Some(signer) => match crate::attestation::sign_decision(signer.as_ref(), &candidate).await {
+                    Ok(a) => Some(a),
+                    Err(e) => {
+                        tracing::warn!("Failed to sign route {}: {}", candidate.route_id, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            let mut proto_route = route_to_proto(candidate);
+            proto_route.attestation = attestation.map(|a| Attestation {
+                key_id: a.key_id,
+                signature: a.signature,
+                signed_at: a.signed_at.to_rfc3339(),
+            });
+            routes.push(proto_route);
+        }
 
         Ok(Response::new(OptimizeResponse {
             success: true,
@@ -75,12 +113,96 @@ impl OptimizerService for OptimizerServiceImpl {
         }))
     }
 
+    async fn batch_optimize_routes(
+        &self,
+        request: Request<BatchOptimizeRequest>,
+    ) -> Result<Response<BatchOptimizeResponse>, Status> {
+        let req = request.into_inner();
+        let start = std::time::Instant::now();
+
+        let graph = self.state.graph.read().await;
+        let constraint_engine = ConstraintEngine::with_cache(self.state.constraint_cache.read().await.clone());
+        let optimizer = Optimizer::with_precomputed_router(constraint_engine, Arc::clone(&self.state.precomputed_router));
+
+        // Bound concurrency so a large batch cannot exhaust the optimizer;
+        // `state.batch_pool` is built once at startup and shared across every
+        // `batch_optimize_routes` call, so aggregate fan-out across the whole
+        // server stays capped at `batch_max_concurrency` rather than each
+        // concurrent call getting its own independent pool of that size.
+        let outcomes: Vec<(i64, i32, BatchOptimizeItem)> = self.state.batch_pool.install(|| {
+            req.requests
+                .par_iter()
+                .map(|proto_req| match parse_optimize_request(proto_req) {
+                    Ok(internal_request) => {
+                        let result = optimizer.optimize(&graph, &internal_request);
+                        let routes: Vec<Route> =
+                            result.routes.into_iter().map(route_to_proto).collect();
+                        (
+                            result.optimization_time_ms as i64,
+                            result.candidates_evaluated as i32,
+                            BatchOptimizeItem {
+                                success: true,
+                                error_message: String::new(),
+                                routes,
+                            },
+                        )
+                    }
+                    Err(e) => (
+                        0,
+                        0,
+                        BatchOptimizeItem {
+                            success: false,
+                            error_message: e.to_string(),
+                            routes: vec![],
+                        },
+                    ),
+                })
+                .collect()
+        });
+
+        let mut total_optimization_time_ms = 0i64;
+        let mut total_candidates_evaluated = 0i32;
+        let mut results = Vec::with_capacity(outcomes.len());
+
+        for (time_ms, candidates, item) in outcomes {
+            total_optimization_time_ms += time_ms;
+            total_candidates_evaluated += candidates;
+            self.state
+                .metrics
+                .optimize_requests
+                .with_label_values(&[if item.success { "success" } else { "failure" }])
+                .inc();
+            results.push(item);
+        }
+        self.state
+            .metrics
+            .candidates_evaluated
+            .inc_by(total_candidates_evaluated as u64);
+
+        tracing::debug!(
+            batch_size = results.len(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "Batch optimization complete"
+        );
+
+        Ok(Response::new(BatchOptimizeResponse {
+            results,
+            total_optimization_time_ms,
+            total_candidates_evaluated,
+        }))
+    }
+
     async fn evaluate_constraints(
         &self,
         request: Request<EvaluateRequest>,
     ) -> Result<Response<EvaluateResponse>, Status> {
         let req = request.into_inner();
 
+        // Version negotiation: unset/1 keeps the original summary-only
+        // response; 2 evaluates soft constraints against the caller's own
+        // `optimize_request` (when supplied) and adds the explain summary.
+        let api_version = if req.api_version >= 2 { 2 } else { 1 };
+
         // Parse route from request
         let Some(proto_route) = req.route else {
             return Err(Status::invalid_argument("Route is required"));
@@ -91,19 +213,32 @@ impl OptimizerService for OptimizerServiceImpl {
             Err(e) => return Err(Status::invalid_argument(e.to_string())),
         };
 
-        // Create constraint engine and evaluate
-        let constraint_engine = ConstraintEngine::new();
-        let default_request = OptimizerRequest::default();
-        let results = constraint_engine.evaluate_route(&route, &default_request);
+        let evaluation_request = if api_version >= 2 {
+            match req.optimize_request.as_ref().map(parse_optimize_request) {
+                Some(Ok(r)) => r,
+                Some(Err(e)) => return Err(Status::invalid_argument(e.to_string())),
+                None => OptimizerRequest::default(),
+            }
+        } else {
+            OptimizerRequest::default()
+        };
+
+        // Dry-run: evaluate the supplied route/request against live
+        // constraint rules without running optimization or touching any cache.
+        let constraint_engine =
+            ConstraintEngine::with_cache(self.state.constraint_cache.read().await.clone());
+        let explanation = constraint_engine.explain_route(&route, &evaluation_request);
 
-        let all_hard_passed = results.iter().filter(|r| r.is_hard).all(|r| r.passed);
-        let overall_score = if results.is_empty() {
+        // Preserve the original (v1) summary fields unchanged for backward compatibility.
+        let all_hard_passed = explanation.results.iter().filter(|r| r.is_hard).all(|r| r.passed);
+        let overall_score = if explanation.results.is_empty() {
             1.0
         } else {
-            results.iter().map(|r| r.score).sum::<f64>() / results.len() as f64
+            explanation.results.iter().map(|r| r.score).sum::<f64>() / explanation.results.len() as f64
         };
 
-        let proto_results: Vec<ConstraintResult> = results
+        let proto_results: Vec<ConstraintResult> = explanation
+            .results
             .into_iter()
             .map(|r| ConstraintResult {
                 constraint_id: r.constraint_id,
@@ -120,6 +255,9 @@ impl OptimizerService for OptimizerServiceImpl {
             all_hard_passed,
             overall_score,
             results: proto_results,
+            api_version: if api_version >= 2 { api_version } else { 0 },
+            hard_constraints_passed: if api_version >= 2 { explanation.hard_constraints_passed } else { false },
+            aggregate_soft_score: if api_version >= 2 { explanation.aggregate_soft_score } else { 0.0 },
         }))
     }
 
@@ -153,20 +291,30 @@ impl OptimizerService for OptimizerServiceImpl {
     ) -> Result<Response<ReloadGraphResponse>, Status> {
         let start = std::time::Instant::now();
 
-        match crate::db::load_graph_from_surrealdb(&self.state.config).await {
-            Ok(new_graph) => {
-                let mut graph = self.state.graph.write().await;
-                *graph = new_graph;
-
-                Ok(Response::new(ReloadGraphResponse {
-                    success: true,
-                    message: format!(
-                        "Loaded {} nodes, {} edges",
-                        graph.node_count(),
-                        graph.edge_count()
-                    ),
-                    load_time_ms: start.elapsed().as_millis() as i64,
-                }))
+        use crate::graph_source::GraphSource;
+        let source =
+            crate::graph_source::default_source(&self.state.config, Arc::clone(&self.state.surreal));
+
+        match source.load(&self.state.config).await {
+            Ok(candidate) => {
+                if crate::try_swap_graph(&self.state, candidate).await {
+                    let graph = self.state.graph.read().await;
+                    Ok(Response::new(ReloadGraphResponse {
+                        success: true,
+                        message: format!(
+                            "Loaded {} nodes, {} edges",
+                            graph.node_count(),
+                            graph.edge_count()
+                        ),
+                        load_time_ms: start.elapsed().as_millis() as i64,
+                    }))
+                } else {
+                    Ok(Response::new(ReloadGraphResponse {
+                        success: false,
+                        message: "Rejected: snapshot failed integrity validation".to_string(),
+                        load_time_ms: start.elapsed().as_millis() as i64,
+                    }))
+                }
             }
             Err(e) => Ok(Response::new(ReloadGraphResponse {
                 success: false,
@@ -175,6 +323,358 @@ impl OptimizerService for OptimizerServiceImpl {
             })),
         }
     }
+
+    async fn find_pareto_routes(
+        &self,
+        request: Request<ParetoRouteRequest>,
+    ) -> Result<Response<ParetoRouteResponse>, Status> {
+        let req = request.into_inner();
+        let constraints = parse_route_constraints_proto(req.constraints.as_ref());
+
+        let graph = self.state.graph.read().await;
+        let routes: Vec<ParetoRouteCandidate> = graph
+            .pareto_paths(&req.origin_code, &req.destination_code, req.weight_kg, &constraints)
+            .into_iter()
+            .map(|r| ParetoRouteCandidate {
+                hops: hops_from_path(&graph, &r.path, &r.edges, req.weight_kg),
+                total_cost_usd: r.total_cost_usd.to_string().parse().unwrap_or(0.0),
+                total_carbon_kg: r.total_carbon_kg,
+                total_transit_hours: r.total_transit_hours,
+                labor_score: r.labor_score,
+            })
+            .collect();
+
+        Ok(Response::new(ParetoRouteResponse {
+            success: true,
+            error_message: String::new(),
+            routes,
+        }))
+    }
+
+    async fn find_fastest_route(
+        &self,
+        request: Request<FastestRouteRequest>,
+    ) -> Result<Response<FastestRouteResponse>, Status> {
+        let req = request.into_inner();
+        let constraints = parse_route_constraints_proto(req.constraints.as_ref());
+
+        let graph = self.state.graph.read().await;
+        match graph.fastest_path(&req.origin_code, &req.destination_code, &constraints) {
+            Some((hops, total_hours)) => Ok(Response::new(FastestRouteResponse {
+                success: true,
+                error_message: String::new(),
+                hops: hops_from_fastest_path(&graph, &hops),
+                total_hours,
+            })),
+            None => Ok(Response::new(FastestRouteResponse {
+                success: false,
+                error_message: "No route exists between the given nodes under the given constraints".to_string(),
+                hops: vec![],
+                total_hours: 0.0,
+            })),
+        }
+    }
+
+    async fn snap_to_network(
+        &self,
+        request: Request<SnapRequest>,
+    ) -> Result<Response<SnapResponse>, Status> {
+        let req = request.into_inner();
+        let filter_modes = parse_transport_modes(&req.filter_modes);
+
+        let graph = self.state.graph.read().await;
+        let nearest = graph
+            .nearest_node(req.lat, req.lon, &filter_modes)
+            .map(snap_node_from_node);
+
+        let within_radius = if req.radius_km > 0.0 {
+            graph
+                .nodes_within_km(req.lat, req.lon, req.radius_km)
+                .into_iter()
+                .map(snap_node_from_node)
+                .collect()
+        } else {
+            vec![]
+        };
+
+        Ok(Response::new(SnapResponse {
+            success: true,
+            error_message: String::new(),
+            nearest,
+            within_radius,
+        }))
+    }
+
+    async fn find_k_shortest_paths(
+        &self,
+        request: Request<KShortestPathsRequest>,
+    ) -> Result<Response<KShortestPathsResponse>, Status> {
+        let req = request.into_inner();
+        let constraints = parse_route_constraints_proto(req.constraints.as_ref());
+
+        let graph = self.state.graph.read().await;
+        let routes: Vec<KShortestPathCandidate> = graph
+            .k_shortest_paths(
+                &req.origin_code,
+                &req.destination_code,
+                req.weight_kg,
+                req.k.max(0) as usize,
+                &constraints,
+            )
+            .into_iter()
+            .map(|(path, edges, cost)| KShortestPathCandidate {
+                hops: hops_from_path(&graph, &path, &edges, req.weight_kg),
+                total_cost_usd: cost.to_string().parse().unwrap_or(0.0),
+            })
+            .collect();
+
+        Ok(Response::new(KShortestPathsResponse {
+            success: true,
+            error_message: String::new(),
+            routes,
+        }))
+    }
+
+    async fn solve_vrp(
+        &self,
+        request: Request<VrpRequest>,
+    ) -> Result<Response<VrpResponse>, Status> {
+        use crate::routing::vrp::{solve, Stop, VrpProblem};
+
+        let req = request.into_inner();
+
+        let departure_time = match DateTime::parse_from_rfc3339(&req.departure_time) {
+            Ok(dt) => dt.with_timezone(&chrono::Utc),
+            Err(e) => {
+                return Ok(Response::new(VrpResponse {
+                    success: false,
+                    error_message: format!("invalid departure_time: {}", e),
+                    stop_sequence: vec![],
+                    legs: vec![],
+                    total_cost_usd: 0.0,
+                    total_carbon_kg: 0.0,
+                    total_distance_km: 0.0,
+                }));
+            }
+        };
+
+        let stops: Vec<Stop> = req
+            .stops
+            .iter()
+            .map(|s| Stop {
+                node_code: s.node_code.clone(),
+                demand_kg: s.demand_kg,
+                time_window: match (&s.window_start, &s.window_end) {
+                    (Some(start), Some(end)) => {
+                        match (DateTime::parse_from_rfc3339(start), DateTime::parse_from_rfc3339(end)) {
+                            (Ok(start), Ok(end)) => {
+                                Some((start.with_timezone(&chrono::Utc), end.with_timezone(&chrono::Utc)))
+                            }
+                            _ => None,
+                        }
+                    }
+                    _ => None,
+                },
+            })
+            .collect();
+
+        let problem = VrpProblem {
+            depot_code: req.depot_code,
+            stops,
+            vehicle_capacity_kg: req.vehicle_capacity_kg,
+            departure_time,
+        };
+
+        let graph = self.state.graph.read().await;
+        match solve(&graph, &problem) {
+            Ok(solution) => {
+                // `VrpSolution` doesn't carry each leg's remaining load, so
+                // it's recomputed here the same way `solve` derives it
+                // internally: starting from total demand and subtracting
+                // each stop's demand as it's delivered.
+                let demand_by_code: std::collections::HashMap<&str, f64> = problem
+                    .stops
+                    .iter()
+                    .map(|s| (s.node_code.as_str(), s.demand_kg))
+                    .collect();
+                let mut remaining_weight_kg: f64 = problem.stops.iter().map(|s| s.demand_kg).sum();
+                let legs = solution
+                    .leg_paths
+                    .iter()
+                    .zip(solution.stop_sequence.iter().map(Some).chain(std::iter::once(None)))
+                    .map(|(path, stop_code)| {
+                        let leg_weight_kg = remaining_weight_kg;
+                        if let Some(code) = stop_code {
+                            remaining_weight_kg -= demand_by_code.get(code.as_str()).copied().unwrap_or(0.0);
+                        }
+                        let edges = edges_for_path(&graph, path, leg_weight_kg);
+                        VrpLeg {
+                            hops: hops_from_path(&graph, path, &edges, leg_weight_kg),
+                        }
+                    })
+                    .collect();
+
+                Ok(Response::new(VrpResponse {
+                    success: true,
+                    error_message: String::new(),
+                    stop_sequence: solution.stop_sequence,
+                    legs,
+                    total_cost_usd: solution.total_cost_usd.to_string().parse().unwrap_or(0.0),
+                    total_carbon_kg: solution.total_carbon_kg,
+                    total_distance_km: solution.total_distance_km,
+                }))
+            }
+            Err(e) => Ok(Response::new(VrpResponse {
+                success: false,
+                error_message: e.to_string(),
+                stop_sequence: vec![],
+                legs: vec![],
+                total_cost_usd: 0.0,
+                total_carbon_kg: 0.0,
+                total_distance_km: 0.0,
+            })),
+        }
+    }
+}
+
+/// Parse a proto mode string (e.g. `"MARITIME"`, case-insensitive) into a
+/// `TransportMode`, or `None` if it isn't one of the four known modes.
+/// Shared by every request field that carries mode names as strings rather
+/// than a proto enum (`allowed_modes`, `filter_modes`, ...).
+fn parse_transport_mode(s: &str) -> Option<TransportMode> {
+    match s.to_uppercase().as_str() {
+        "MARITIME" => Some(TransportMode::Maritime),
+        "RAIL" => Some(TransportMode::Rail),
+        "ROAD" => Some(TransportMode::Road),
+        "AIR" => Some(TransportMode::Air),
+        _ => None,
+    }
+}
+
+/// Like `parse_transport_mode`, but for a whole repeated-string field at
+/// once — used by RPCs that take `filter_modes` as a `Vec<TransportMode>`
+/// rather than a `HashSet` (e.g. `nearest_node`'s `filter_modes` parameter).
+fn parse_transport_modes(modes: &[String]) -> Vec<TransportMode> {
+    modes.iter().filter_map(|m| parse_transport_mode(m)).collect()
+}
+
+/// Convert a `TransportNode` into its `SnapToNetwork` proto representation.
+fn snap_node_from_node(node: &crate::graph::TransportNode) -> SnapNode {
+    SnapNode {
+        code: node.code.clone(),
+        name: node.name.clone(),
+        lat: node.lat,
+        lon: node.lon,
+        modes: node.modes.iter().map(|m| m.to_string()).collect(),
+    }
+}
+
+/// Build a `RouteConstraints` from its proto mirror, defaulting to "no
+/// restriction" (matching `RouteConstraints::default()`) when the caller
+/// omits the field entirely.
+fn parse_route_constraints_proto(proto: Option<&RouteConstraintsProto>) -> RouteConstraints {
+    let Some(proto) = proto else {
+        return RouteConstraints::default();
+    };
+
+    let allowed_modes = proto.allowed_modes.iter().filter_map(|m| parse_transport_mode(m)).collect();
+
+    RouteConstraints {
+        min_safety_rating: proto.min_safety_rating,
+        exclude_sanctioned: proto.exclude_sanctioned,
+        require_unionized: proto.require_unionized,
+        exclude_inactive: proto.exclude_inactive,
+        allowed_modes,
+        max_transit_hours: proto.max_transit_hours,
+        max_carbon_budget: proto.max_carbon_budget,
+    }
+}
+
+/// Convert a `(path, edges)` pair returned by one of `TransportGraph`'s
+/// search methods into the hop sequence shared by the Pareto/fastest/
+/// k-shortest RPCs. `edges[i]` is the edge taken to reach `path[i + 1]`, so
+/// the origin (`path[0]`) always gets an empty `edge_code`/`mode`. `weight_kg`
+/// is the shipment weight used to price each edge's cost/carbon; pass `0.0`
+/// when it isn't known (e.g. `fastest_path`, which has no weight context).
+fn hops_from_path(
+    graph: &TransportGraph,
+    path: &[NodeIndex],
+    edges: &[&TransportEdge],
+    weight_kg: f64,
+) -> Vec<PathHop> {
+    path.iter()
+        .enumerate()
+        .map(|(i, &node_idx)| {
+            let node_code = graph.inner()[node_idx].code.clone();
+            match i.checked_sub(1).and_then(|prev| edges.get(prev)) {
+                Some(edge) => PathHop {
+                    node_code,
+                    edge_code: edge.code.clone(),
+                    mode: edge.mode.to_string(),
+                    distance_km: edge.distance_km,
+                    cost_usd: edge.calculate_cost(weight_kg).to_string().parse().unwrap_or(0.0),
+                    transit_hours: edge.transit_hours,
+                    carbon_kg: edge.calculate_carbon(weight_kg),
+                },
+                None => PathHop {
+                    node_code,
+                    edge_code: String::new(),
+                    mode: String::new(),
+                    distance_km: 0.0,
+                    cost_usd: 0.0,
+                    transit_hours: 0.0,
+                    carbon_kg: 0.0,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Convert a `fastest_path` result into the shared `PathHop` sequence.
+/// `fastest_path` carries no shipment weight, so (unlike `hops_from_path`)
+/// `cost_usd`/`carbon_kg` are always left at `0.0` here.
+fn hops_from_fastest_path(graph: &TransportGraph, hops: &[crate::graph::FastestPathHop<'_>]) -> Vec<PathHop> {
+    hops.iter()
+        .map(|hop| {
+            let node_code = graph.inner()[hop.node].code.clone();
+            match hop.edge {
+                Some(edge) => PathHop {
+                    node_code,
+                    edge_code: edge.code.clone(),
+                    mode: edge.mode.to_string(),
+                    distance_km: edge.distance_km,
+                    cost_usd: 0.0,
+                    transit_hours: edge.transit_hours,
+                    carbon_kg: 0.0,
+                },
+                None => PathHop {
+                    node_code,
+                    edge_code: String::new(),
+                    mode: String::new(),
+                    distance_km: 0.0,
+                    cost_usd: 0.0,
+                    transit_hours: 0.0,
+                    carbon_kg: 0.0,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Reconstruct the cheapest edge taken on each hop of a plain node path,
+/// e.g. one of `VrpSolution::leg_paths`, which (unlike `shortest_path_astar`'s
+/// result) carries node indices only. Ties are broken the same way
+/// `shortest_path_astar` breaks them: cheapest edge by `calculate_cost`.
+fn edges_for_path<'a>(graph: &'a TransportGraph, path: &[NodeIndex], weight_kg: f64) -> Vec<&'a TransportEdge> {
+    path.windows(2)
+        .filter_map(|pair| {
+            graph
+                .inner()
+                .edges_connecting(pair[0], pair[1])
+                .min_by_key(|e| e.weight().calculate_cost(weight_kg))
+                .map(|e| e.weight())
+        })
+        .collect()
 }
 
 /// Create the gRPC server
@@ -194,17 +694,8 @@ fn parse_optimize_request(req: &OptimizeRequest) -> Result<OptimizerRequest, any
         .map(|dt| dt.with_timezone(&chrono::Utc))
         .unwrap_or_else(|_| chrono::Utc::now() + chrono::Duration::days(30));
 
-    let allowed_modes: std::collections::HashSet<TransportMode> = req
-        .allowed_modes
-        .iter()
-        .filter_map(|m| match m.to_uppercase().as_str() {
-            "MARITIME" => Some(TransportMode::Maritime),
-            "RAIL" => Some(TransportMode::Rail),
-            "ROAD" => Some(TransportMode::Road),
-            "AIR" => Some(TransportMode::Air),
-            _ => None,
-        })
-        .collect();
+    let allowed_modes: std::collections::HashSet<TransportMode> =
+        req.allowed_modes.iter().filter_map(|m| parse_transport_mode(m)).collect();
 
     Ok(OptimizerRequest {
         shipment_id: req.shipment_id.clone(),
@@ -225,6 +716,18 @@ fn parse_optimize_request(req: &OptimizeRequest) -> Result<OptimizerRequest, any
         time_weight: req.time_weight,
         carbon_weight: req.carbon_weight,
         labor_weight: req.labor_weight,
+        heuristic: match req.heuristic.to_uppercase().as_str() {
+            "ASTAR" => crate::optimizer::HeuristicKind::AStar,
+            _ => crate::optimizer::HeuristicKind::Dijkstra,
+        },
+        // `0` (the unset-field zero value) would silently disable the
+        // heuristic under `AStar`, so it's treated as "not set" and mapped
+        // to the optimal-search default instead.
+        greedy_factor: if req.greedy_factor == 0.0 { 1.0 } else { req.greedy_factor },
+        waypoints: req.waypoints.clone(),
+        arrival_weight: req.arrival_weight,
+        maximize_slack: req.maximize_slack,
+        lns_iterations: req.lns_iterations.max(0) as usize,
     })
 }
 
@@ -275,6 +778,10 @@ fn route_to_proto(route: CandidateRoute) -> Route {
         pareto_optimal: route.pareto_optimal,
         weighted_score: route.weighted_score,
         constraint_results,
+        attestation: None,
+        crowding_distance: route.crowding_distance,
+        earliest_arrival: route.earliest_arrival.to_rfc3339(),
+        schedule_slack_hours: route.schedule_slack_hours,
     }
 }
 
@@ -317,6 +824,21 @@ fn parse_proto_route(proto: &Route) -> Result<CandidateRoute, anyhow::Error> {
         })
         .collect();
 
+    // Prefer the proto's own `earliest_arrival`, when set; older callers
+    // that predate that field leave it empty, so fall back to recomputing
+    // it from the reconstructed segments the same way this function always
+    // has.
+    let earliest_arrival = DateTime::parse_from_rfc3339(&proto.earliest_arrival)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .unwrap_or_else(|_| {
+            proto
+                .segments
+                .last()
+                .and_then(|s| DateTime::parse_from_rfc3339(&s.arrival_time).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_else(chrono::Utc::now)
+        });
+
     let mut route = CandidateRoute {
         route_id: proto.route_id.clone(),
         segments,
@@ -325,8 +847,11 @@ fn parse_proto_route(proto: &Route) -> Result<CandidateRoute, anyhow::Error> {
         total_carbon_kg: proto.total_carbon_kg,
         total_distance_km: proto.total_distance_km,
         labor_score: proto.labor_score,
+        earliest_arrival,
+        schedule_slack_hours: proto.schedule_slack_hours,
         pareto_rank: proto.pareto_rank as u32,
         pareto_optimal: proto.pareto_optimal,
+        crowding_distance: proto.crowding_distance,
         weighted_score: proto.weighted_score,
         constraint_results: vec![],
     };
@@ -8,8 +8,16 @@ mod optimizer;
 mod constraints;
 mod grpc;
 mod db;
+mod metrics;
+mod graph_source;
+mod constraint_source;
+mod bench;
+mod attestation;
+mod routing;
+mod precomputed;
+mod fallback;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, Level};
@@ -29,6 +37,37 @@ pub struct Config {
     pub dragonfly_url: String,
     pub dragonfly_pass: Option<String>,
     pub graph_reload_interval_secs: u64,
+    pub batch_max_concurrency: usize,
+    pub graph_snapshot_path: String,
+    pub graph_live_updates: bool,
+    pub constraint_rules_path: String,
+    pub constraint_rules_reload_interval_secs: u64,
+    /// Content hashes (see `TransportGraph::content_hash`) of snapshots known
+    /// to be bad, skipped immediately rather than re-validated.
+    pub graph_snapshot_hash_blacklist: std::collections::HashSet<u64>,
+    /// Whether emitted optimization decisions should be signed (see
+    /// `attestation`). Off by default, since it requires a signer to be
+    /// configured below.
+    pub attestation_enabled: bool,
+    /// Key id attached alongside every signature, so a verifier knows which
+    /// public key to check it against.
+    pub attestation_key_id: String,
+    /// Hex-encoded 32-byte Ed25519 seed for local signing. Ignored if
+    /// `attestation_remote_signer_url` is set.
+    pub attestation_local_seed: Option<String>,
+    /// URL of an external signer (e.g. a KMS-backed signing endpoint) to use
+    /// instead of a local keypair, so the private key never lives here.
+    pub attestation_remote_signer_url: Option<String>,
+    /// Hex-encoded Ed25519 public key matching whichever signer is in use,
+    /// published so auditors can verify stored decisions offline via
+    /// `attestation::run_verify`.
+    pub attestation_public_key: Option<String>,
+    /// Hub node codes (major ports, sortation centers) to keep precomputed
+    /// cheapest-path trees for. See `precomputed::PrecomputedRouter`.
+    pub precomputed_hub_codes: Vec<String>,
+    /// Path to the on-disk `PrecomputedRouter` cache snapshot, loaded on
+    /// startup and overwritten after every recompute.
+    pub precomputed_cache_path: String,
 }
 
 impl Config {
@@ -54,6 +93,40 @@ impl Config {
             graph_reload_interval_secs: std::env::var("GRAPH_RELOAD_INTERVAL")
                 .unwrap_or_else(|_| "300".to_string())
                 .parse()?,
+            batch_max_concurrency: std::env::var("BATCH_MAX_CONCURRENCY")
+                .unwrap_or_else(|_| "8".to_string())
+                .parse()?,
+            graph_snapshot_path: std::env::var("GRAPH_SNAPSHOT_PATH")
+                .unwrap_or_else(|_| "graph_snapshot.json".to_string()),
+            graph_live_updates: std::env::var("GRAPH_LIVE_UPDATES")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            constraint_rules_path: std::env::var("CONSTRAINT_RULES_PATH")
+                .unwrap_or_else(|_| "constraint_rules_snapshot.json".to_string()),
+            constraint_rules_reload_interval_secs: std::env::var("CONSTRAINT_RULES_RELOAD_INTERVAL")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()?,
+            graph_snapshot_hash_blacklist: std::env::var("GRAPH_SNAPSHOT_HASH_BLACKLIST")
+                .unwrap_or_default()
+                .split(',')
+                .filter_map(|s| s.trim().parse::<u64>().ok())
+                .collect(),
+            attestation_enabled: std::env::var("ATTESTATION_ENABLED")
+                .map(|v| v == "true" || v == "1")
+                .unwrap_or(false),
+            attestation_key_id: std::env::var("ATTESTATION_KEY_ID")
+                .unwrap_or_else(|_| "veds-default".to_string()),
+            attestation_local_seed: std::env::var("ATTESTATION_LOCAL_SEED").ok(),
+            attestation_remote_signer_url: std::env::var("ATTESTATION_REMOTE_SIGNER_URL").ok(),
+            attestation_public_key: std::env::var("ATTESTATION_PUBLIC_KEY").ok(),
+            precomputed_hub_codes: std::env::var("PRECOMPUTED_HUB_CODES")
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            precomputed_cache_path: std::env::var("PRECOMPUTED_CACHE_PATH")
+                .unwrap_or_else(|_| "precomputed_router.json".to_string()),
         })
     }
 }
@@ -63,6 +136,77 @@ pub struct AppState {
     pub config: Config,
     pub graph: RwLock<TransportGraph>,
     pub redis: redis::aio::ConnectionManager,
+    pub metrics: metrics::Metrics,
+    pub surreal: Arc<db::SurrealConnection>,
+    pub constraint_cache: Arc<RwLock<crate::constraints::ConstraintCache>>,
+    /// Grows with every snapshot rejected for failing `TransportGraph::validate`,
+    /// seeded from `Config::graph_snapshot_hash_blacklist`.
+    pub graph_snapshot_blacklist: Arc<RwLock<std::collections::HashSet<u64>>>,
+    /// Signs emitted optimization decisions when attestation is configured;
+    /// `None` leaves decisions unsigned, same as before this feature existed.
+    pub signer: Option<Arc<dyn attestation::Signer>>,
+    /// Cached cheapest-path trees for `Config::precomputed_hub_codes`. A
+    /// `std::sync::RwLock`, not `tokio::sync::RwLock`, because it's read
+    /// from `Optimizer::optimize`'s synchronous code path, which must not
+    /// ever go through an async lock's `blocking_read`.
+    pub precomputed_router: Arc<std::sync::RwLock<precomputed::PrecomputedRouter>>,
+    /// Bounds `batch_optimize_routes`' fan-out to `Config::batch_max_concurrency`
+    /// worker threads, built once at startup and reused across calls so
+    /// concurrent batch requests share one bound instead of each paying for
+    /// (and stacking) their own dedicated pool.
+    pub batch_pool: rayon::ThreadPool,
+}
+
+/// Validate and swap a freshly loaded graph snapshot into the live
+/// `RwLock<TransportGraph>`, rejecting it (and keeping the previous
+/// last-good graph) if its content hash is blacklisted or it fails
+/// `TransportGraph::validate`. A newly-failing hash is added to the
+/// blacklist so a repeatedly-bad manifest is skipped immediately next time.
+/// Returns whether the swap happened.
+pub(crate) async fn try_swap_graph(state: &Arc<AppState>, candidate: TransportGraph) -> bool {
+    let hash = candidate.content_hash();
+
+    if state.graph_snapshot_blacklist.read().await.contains(&hash) {
+        tracing::warn!(hash, "Rejected graph snapshot: known-bad hash (blacklisted)");
+        state
+            .metrics
+            .graph_reload_rejected
+            .with_label_values(&["blacklisted"])
+            .inc();
+        return false;
+    }
+
+    if let Err(e) = candidate.validate() {
+        tracing::warn!(hash, "Rejected graph snapshot: {}", e);
+        state
+            .metrics
+            .graph_reload_rejected
+            .with_label_values(&["invalid"])
+            .inc();
+        state.graph_snapshot_blacklist.write().await.insert(hash);
+        return false;
+    }
+
+    let mut graph = state.graph.write().await;
+    *graph = candidate;
+    state.metrics.observe_graph_load(&graph);
+
+    // A whole-graph swap can change or drop any edge, so every cached tree
+    // is suspect; recompute from the freshly swapped-in graph rather than
+    // trying to reason about which ones are still valid.
+    {
+        let mut router = state.precomputed_router.write().unwrap();
+        router.invalidate_all();
+        router.precompute_all(
+            &graph,
+            &precomputed::default_mode_subsets(),
+            &precomputed::default_weight_samples_kg(),
+        );
+        let _ = router.save(std::path::Path::new(&state.config.precomputed_cache_path));
+    }
+    state.metrics.observe_precomputed_router(&state.precomputed_router.read().unwrap());
+
+    true
 }
 
 #[tokio::main]
@@ -77,6 +221,18 @@ async fn main() -> Result<()> {
         )
         .init();
 
+    // `bench <workload.json|dir> [--report-url <url>]` replays recorded
+    // workloads against the optimizer instead of starting the server.
+    let cli_args: Vec<String> = std::env::args().collect();
+    if cli_args.get(1).map(String::as_str) == Some("bench") {
+        return bench::run(&cli_args[2..]).await;
+    }
+    // `verify <record.json> [--public-key <hex>]` checks a stored decision's
+    // attestation offline, without starting the server.
+    if cli_args.get(1).map(String::as_str) == Some("verify") {
+        return attestation::run_verify(&cli_args[2..]).await;
+    }
+
     info!("Starting VEDS Route Optimizer");
 
     // Load configuration
@@ -94,34 +250,112 @@ async fn main() -> Result<()> {
         config.dragonfly_url.clone()
     };
     let redis_client = redis::Client::open(redis_url)?;
-    let redis_conn = redis::aio::ConnectionManager::new(redis_client).await?;
+    let mut redis_conn = redis::aio::ConnectionManager::new(redis_client.clone()).await?;
     info!("Connected to Dragonfly/Redis");
 
     // Initialize transport graph
     let graph = TransportGraph::new();
     info!("Transport graph initialized (empty)");
 
+    // Load the constraint cache (sanction lists, minimum wages, and
+    // operator-registered custom rules), preferring Dragonfly with a
+    // fallback to the last-good on-disk snapshot if it is unreachable.
+    let constraint_cache = {
+        use constraint_source::ConstraintRuleSource;
+        let source = constraint_source::default_rule_source(&config, redis_client.clone());
+        match source.load_rules().await {
+            Ok(cache) => cache,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load constraint cache: {}. Starting with an empty cache.",
+                    e
+                );
+                crate::constraints::ConstraintCache::default()
+            }
+        }
+    };
+
+    // Load the precomputed-path-tree cache, preferring the last-good
+    // on-disk snapshot and otherwise starting empty — `try_swap_graph`
+    // fills it in once the initial graph load below completes.
+    let precomputed_router = precomputed::PrecomputedRouter::load_or_new(
+        std::path::Path::new(&config.precomputed_cache_path),
+        config.precomputed_hub_codes.clone(),
+    );
+
+    // Built once and shared across calls so concurrent `batch_optimize_routes`
+    // RPCs bound their *aggregate* fan-out to `batch_max_concurrency`, rather
+    // than each call spinning up (and tearing down) its own dedicated pool.
+    let batch_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.batch_max_concurrency)
+        .build()
+        .context("Failed to build batch optimization thread pool")?;
+
     // Create shared state
     let state = Arc::new(AppState {
         config: config.clone(),
         graph: RwLock::new(graph),
         redis: redis_conn,
+        metrics: metrics::Metrics::new(),
+        surreal: Arc::new(db::SurrealConnection::new()),
+        constraint_cache: Arc::new(RwLock::new(constraint_cache)),
+        graph_snapshot_blacklist: Arc::new(RwLock::new(config.graph_snapshot_hash_blacklist.clone())),
+        signer: attestation::default_signer(&config),
+        precomputed_router: Arc::new(std::sync::RwLock::new(precomputed_router)),
+        batch_pool,
     });
 
-    // Load initial graph from database
+    // Spawn background task keeping the constraint cache live via Dragonfly
+    // keyspace notifications, so sanction lists and wage thresholds take
+    // effect without a full restart.
+    let constraint_cache_clone = Arc::clone(&state.constraint_cache);
+    tokio::spawn(db::watch_constraint_cache(redis_client.clone(), constraint_cache_clone));
+
+    // Periodically re-run the full `ConstraintRuleSource` chain as a fallback
+    // resync, the same way the graph reload task backstops live graph
+    // updates: keyspace notifications above only cover the built-in wage and
+    // sanction fields, not runtime-registered custom rules.
+    let state_clone = Arc::clone(&state);
+    let redis_client_for_rules = redis_client.clone();
+    tokio::spawn(async move {
+        use constraint_source::ConstraintRuleSource;
+        let source = constraint_source::default_rule_source(&state_clone.config, redis_client_for_rules);
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(
+            state_clone.config.constraint_rules_reload_interval_secs,
+        ));
+        loop {
+            interval.tick().await;
+            match source.load_rules().await {
+                Ok(new_cache) => {
+                    let mut cache = state_clone.constraint_cache.write().await;
+                    *cache = new_cache;
+                    info!("Constraint rules reloaded");
+                }
+                Err(e) => tracing::warn!("Failed to reload constraint rules: {}", e),
+            }
+        }
+    });
+
+    // Load initial graph, preferring SurrealDB with a fallback to the
+    // last-good on-disk snapshot if the database is unreachable. The loaded
+    // snapshot is hash-checked against the blacklist and validated before it
+    // replaces the (empty) in-memory graph.
+    let source = graph_source::default_source(&config, Arc::clone(&state.surreal));
     {
-        let mut graph = state.graph.write().await;
-        match db::load_graph_from_surrealdb(&config).await {
-            Ok(loaded_graph) => {
-                *graph = loaded_graph;
-                info!(
-                    nodes = graph.node_count(),
-                    edges = graph.edge_count(),
-                    "Transport graph loaded from SurrealDB"
-                );
+        use graph_source::GraphSource;
+        match source.load(&config).await {
+            Ok(candidate) => {
+                if try_swap_graph(&state, candidate).await {
+                    let graph = state.graph.read().await;
+                    info!(
+                        nodes = graph.node_count(),
+                        edges = graph.edge_count(),
+                        "Transport graph loaded from SurrealDB"
+                    );
+                }
             }
             Err(e) => {
-                tracing::warn!("Failed to load graph from SurrealDB: {}. Starting with empty graph.", e);
+                tracing::warn!("Failed to load transport graph: {}. Starting with empty graph.", e);
             }
         }
     }
@@ -129,25 +363,36 @@ async fn main() -> Result<()> {
     // Spawn background graph reload task
     let state_clone = Arc::clone(&state);
     tokio::spawn(async move {
+        use graph_source::GraphSource;
+        let source = graph_source::default_source(&state_clone.config, Arc::clone(&state_clone.surreal));
         let mut interval = tokio::time::interval(
             std::time::Duration::from_secs(state_clone.config.graph_reload_interval_secs)
         );
         loop {
             interval.tick().await;
-            if let Ok(new_graph) = db::load_graph_from_surrealdb(&state_clone.config).await {
-                let mut graph = state_clone.graph.write().await;
-                *graph = new_graph;
-                info!("Transport graph reloaded");
+            if let Ok(candidate) = source.load(&state_clone.config).await {
+                if try_swap_graph(&state_clone, candidate).await {
+                    info!("Transport graph reloaded");
+                }
             }
         }
     });
 
+    // Optionally keep the graph fresh via SurrealDB live queries instead of
+    // waiting for the next periodic full reload; the task above remains the
+    // full-resync fallback if the live stream drops.
+    if config.graph_live_updates {
+        tokio::spawn(db::watch_live_graph_updates(Arc::clone(&state)));
+    }
+
     // Spawn metrics server
     let metrics_port = config.metrics_port;
+    let metrics_state = Arc::clone(&state);
     tokio::spawn(async move {
         let app = axum::Router::new()
             .route("/metrics", axum::routing::get(metrics_handler))
-            .route("/health", axum::routing::get(health_handler));
+            .route("/health", axum::routing::get(health_handler))
+            .with_state(metrics_state);
 
         let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", metrics_port))
             .await
@@ -168,10 +413,12 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn metrics_handler() -> String {
+async fn metrics_handler(
+    axum::extract::State(state): axum::extract::State<Arc<AppState>>,
+) -> String {
     use prometheus::Encoder;
     let encoder = prometheus::TextEncoder::new();
-    let metric_families = prometheus::gather();
+    let metric_families = state.metrics.registry.gather();
     let mut buffer = Vec::new();
     encoder.encode(&metric_families, &mut buffer).unwrap();
     String::from_utf8(buffer).unwrap()
@@ -0,0 +1,226 @@
+//! Prometheus Metrics
+//!
+//! Instruments the optimizer and graph-loading hot paths so operators can
+//! observe optimizer throughput and graph freshness without scraping logs,
+//! mirroring the admin metrics a storage service would export.
+
+use prometheus::{
+    Histogram, HistogramOpts, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts, Registry,
+};
+
+/// Prometheus metrics for the route optimizer, held in `AppState` and
+/// recorded into on each RPC.
+pub struct Metrics {
+    pub registry: Registry,
+    /// `optimize_routes` calls labeled by outcome ("success"/"failure")
+    pub optimize_requests: IntCounterVec,
+    /// `result.optimization_time_ms` for each `optimize_routes` call
+    pub optimization_time_ms: Histogram,
+    /// Running total of candidate routes evaluated across all requests
+    pub candidates_evaluated: IntCounter,
+    /// `load_time_ms` for each transport graph (re)load from SurrealDB
+    pub graph_load_time_ms: Histogram,
+    /// Current node count of the in-memory transport graph
+    pub graph_node_count: IntGauge,
+    /// Current edge count of the in-memory transport graph
+    pub graph_edge_count: IntGauge,
+    /// Current edge count broken down by transport mode
+    pub graph_edge_count_by_mode: IntGaugeVec,
+    /// Graph (re)loads rejected before swap, labeled by reason
+    /// ("blacklisted"/"invalid")
+    pub graph_reload_rejected: IntCounterVec,
+    /// Cumulative `PrecomputedRouter::lookup` hits
+    pub precomputed_cache_hits: IntGauge,
+    /// Cumulative `PrecomputedRouter::lookup` misses
+    pub precomputed_cache_misses: IntGauge,
+    /// Current number of cached cheapest-path trees
+    pub precomputed_tree_count: IntGauge,
+    /// Age of the stalest cached tree, in seconds
+    pub precomputed_oldest_tree_age_seconds: IntGauge,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new();
+
+        let optimize_requests = IntCounterVec::new(
+            Opts::new(
+                "veds_optimize_requests_total",
+                "Total optimize_routes calls by outcome",
+            ),
+            &["result"],
+        )
+        .unwrap();
+
+        let optimization_time_ms = Histogram::with_opts(
+            HistogramOpts::new(
+                "veds_optimization_time_ms",
+                "optimize_routes wall-clock time in milliseconds",
+            )
+            .buckets(vec![
+                1.0, 5.0, 10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0,
+            ]),
+        )
+        .unwrap();
+
+        let candidates_evaluated = IntCounter::new(
+            "veds_candidates_evaluated_total",
+            "Total candidate routes evaluated across all optimize_routes calls",
+        )
+        .unwrap();
+
+        let graph_load_time_ms = Histogram::with_opts(
+            HistogramOpts::new(
+                "veds_graph_load_time_ms",
+                "Time to load the transport graph from SurrealDB in milliseconds",
+            )
+            .buckets(vec![
+                10.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0,
+            ]),
+        )
+        .unwrap();
+
+        let graph_node_count = IntGauge::new(
+            "veds_graph_node_count",
+            "Current number of nodes in the transport graph",
+        )
+        .unwrap();
+
+        let graph_edge_count = IntGauge::new(
+            "veds_graph_edge_count",
+            "Current number of edges in the transport graph",
+        )
+        .unwrap();
+
+        let graph_edge_count_by_mode = IntGaugeVec::new(
+            Opts::new(
+                "veds_graph_edge_count_by_mode",
+                "Current number of edges in the transport graph, by mode",
+            ),
+            &["mode"],
+        )
+        .unwrap();
+
+        let graph_reload_rejected = IntCounterVec::new(
+            Opts::new(
+                "veds_graph_reload_rejected_total",
+                "Graph (re)loads rejected before swap, by reason",
+            ),
+            &["reason"],
+        )
+        .unwrap();
+
+        let precomputed_cache_hits = IntGauge::new(
+            "veds_precomputed_cache_hits_total",
+            "Cumulative PrecomputedRouter lookup hits",
+        )
+        .unwrap();
+
+        let precomputed_cache_misses = IntGauge::new(
+            "veds_precomputed_cache_misses_total",
+            "Cumulative PrecomputedRouter lookup misses",
+        )
+        .unwrap();
+
+        let precomputed_tree_count = IntGauge::new(
+            "veds_precomputed_tree_count",
+            "Current number of cached precomputed cheapest-path trees",
+        )
+        .unwrap();
+
+        let precomputed_oldest_tree_age_seconds = IntGauge::new(
+            "veds_precomputed_oldest_tree_age_seconds",
+            "Age in seconds of the stalest cached precomputed tree",
+        )
+        .unwrap();
+
+        registry
+            .register(Box::new(optimize_requests.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(optimization_time_ms.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(candidates_evaluated.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(graph_load_time_ms.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(graph_node_count.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(graph_edge_count.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(graph_edge_count_by_mode.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(graph_reload_rejected.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(precomputed_cache_hits.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(precomputed_cache_misses.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(precomputed_tree_count.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(precomputed_oldest_tree_age_seconds.clone()))
+            .unwrap();
+
+        Metrics {
+            registry,
+            optimize_requests,
+            optimization_time_ms,
+            candidates_evaluated,
+            graph_load_time_ms,
+            graph_node_count,
+            graph_edge_count,
+            graph_edge_count_by_mode,
+            graph_reload_rejected,
+            precomputed_cache_hits,
+            precomputed_cache_misses,
+            precomputed_tree_count,
+            precomputed_oldest_tree_age_seconds,
+        }
+    }
+
+    /// Refresh the graph freshness gauges from the current transport graph
+    pub fn observe_graph(&self, graph: &crate::graph::TransportGraph) {
+        self.graph_node_count.set(graph.node_count() as i64);
+        self.graph_edge_count.set(graph.edge_count() as i64);
+        for (mode, count) in graph.edge_count_by_mode() {
+            self.graph_edge_count_by_mode
+                .with_label_values(&[&mode.to_string()])
+                .set(count as i64);
+        }
+    }
+
+    /// Record a completed graph load
+    pub fn observe_graph_load(&self, graph: &crate::graph::TransportGraph) {
+        self.graph_load_time_ms.observe(graph.load_time_ms as f64);
+        self.observe_graph(graph);
+    }
+
+    /// Refresh the precomputed-cache gauges from the current router state
+    pub fn observe_precomputed_router(&self, router: &crate::precomputed::PrecomputedRouter) {
+        self.precomputed_cache_hits.set(router.hit_count() as i64);
+        self.precomputed_cache_misses.set(router.miss_count() as i64);
+        self.precomputed_tree_count.set(router.tree_count() as i64);
+        self.precomputed_oldest_tree_age_seconds.set(
+            router
+                .oldest_tree_age()
+                .map(|age| age.num_seconds())
+                .unwrap_or(0),
+        );
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,500 @@
+//! Precomputed Shortest-Path Trees
+//!
+//! A handful of major hubs (large ports, sortation centers) serve as the
+//! origin for a disproportionate share of shipments, yet `optimize` re-runs
+//! a full Dijkstra from scratch for every one of them. `PrecomputedRouter`
+//! builds and caches a single-source cheapest-path tree per hub, keyed by
+//! the allowed-mode subset and a `weight_kg` bucket (since
+//! `TransportEdge::calculate_cost`/`calculate_carbon` both scale with
+//! weight), so a request whose origin is a configured hub can stitch its
+//! shortest path straight out of the cached tree instead of searching.
+//! Trees serialize to disk via serde so a warm restart doesn't pay to
+//! recompute them. Edge-level invalidation keeps the cache from ever
+//! returning a path over an edge that's gone inactive, sanctioned, or had
+//! its pricing change.
+
+use crate::graph::{TransportEdge, TransportGraph, TransportMode};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Buckets `weight_kg` into coarse, shared tiers so the cache doesn't need
+/// one tree per distinct shipment weight. Cost is `base_cost_usd +
+/// cost_per_kg * weight_kg`, so nearby weights within a tier produce a
+/// cheapest-path tree that's "close enough" for the cache to be useful. A
+/// cached path is not re-optimized for the exact `weight_kg`: the caller
+/// re-checks only its segment count and arrival time.
+fn weight_bucket(weight_kg: f64) -> u32 {
+    match weight_kg {
+        w if w <= 100.0 => 0,
+        w if w <= 1_000.0 => 1,
+        w if w <= 10_000.0 => 2,
+        _ => 3,
+    }
+}
+
+/// Representative mode subsets to precompute a tree for: unrestricted, plus
+/// one tree per individual mode, covering the overwhelmingly common request
+/// shapes. A request with some other combination simply falls back to a
+/// live search via `lookup`'s miss path.
+pub fn default_mode_subsets() -> Vec<HashSet<TransportMode>> {
+    vec![
+        HashSet::new(),
+        HashSet::from([TransportMode::Maritime]),
+        HashSet::from([TransportMode::Rail]),
+        HashSet::from([TransportMode::Road]),
+        HashSet::from([TransportMode::Air]),
+    ]
+}
+
+/// One representative weight per `weight_bucket` tier, for `precompute_all`
+/// to build a tree against.
+pub fn default_weight_samples_kg() -> Vec<f64> {
+    vec![50.0, 500.0, 5_000.0, 50_000.0]
+}
+
+/// Canonical key for an allowed-mode subset: `"ALL"` for the common
+/// "no restriction" case (an empty `allowed_modes`), otherwise the sorted
+/// mode names joined with `,` so `{Road, Rail}` and `{Rail, Road}` hash the
+/// same tree.
+fn mode_key(allowed_modes: &HashSet<TransportMode>) -> String {
+    if allowed_modes.is_empty() {
+        return "ALL".to_string();
+    }
+    let mut names: Vec<&'static str> = allowed_modes
+        .iter()
+        .map(|m| match m {
+            TransportMode::Maritime => "MARITIME",
+            TransportMode::Rail => "RAIL",
+            TransportMode::Road => "ROAD",
+            TransportMode::Air => "AIR",
+        })
+        .collect();
+    names.sort_unstable();
+    names.join(",")
+}
+
+/// Identifies one cached tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TreeKey {
+    pub hub_code: String,
+    pub mode_key: String,
+    pub weight_bucket: u32,
+}
+
+/// A single-source cheapest-path tree rooted at `TreeKey::hub_code`: for
+/// every other reachable node code, the edge that reaches it most cheaply
+/// from the hub and the node code it comes from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrecomputedTree {
+    predecessor: HashMap<String, (String, TransportEdge)>,
+    /// Every edge code that was a node's cheapest-known incoming edge at
+    /// some point while building this tree (a superset of the edges on its
+    /// stored paths), so `invalidate_edge` can cheaply tell whether a
+    /// changed edge lies on any cached path without walking every stored
+    /// path. An edge outside this set is not tracked, even if a price drop
+    /// on it would change the cheapest tree.
+    edge_codes: HashSet<String>,
+    pub computed_at: DateTime<Utc>,
+}
+
+impl PrecomputedTree {
+    /// Walks the predecessor chain from `destination_code` back to the hub,
+    /// returning the path in hub-to-destination order. `None` if
+    /// `destination_code` wasn't reached while building the tree.
+    fn path_to(&self, destination_code: &str) -> Option<Vec<(String, TransportEdge)>> {
+        let mut reversed = Vec::new();
+        let mut current = destination_code.to_string();
+
+        while let Some((prev, edge)) = self.predecessor.get(&current) {
+            reversed.push((current.clone(), edge.clone()));
+            current = prev.clone();
+        }
+
+        reversed.reverse();
+        if reversed.is_empty() {
+            None
+        } else {
+            Some(reversed)
+        }
+    }
+}
+
+/// Ordered by cost (reversed, so `BinaryHeap` pops the cheapest first —
+/// the same min-heap idiom `optimizer::SearchState` uses).
+struct DijkstraState {
+    node_code: String,
+    cost: rust_decimal::Decimal,
+}
+
+impl Eq for DijkstraState {}
+impl PartialEq for DijkstraState {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+impl Ord for DijkstraState {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.cost.cmp(&self.cost)
+    }
+}
+impl PartialOrd for DijkstraState {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// On-disk snapshot of a `PrecomputedRouter`. Plain `HashMap<TreeKey, _>`
+/// doesn't round-trip through `serde_json` (non-string map keys), so the
+/// cache is flattened to a `Vec` of pairs for (de)serialization and
+/// rebuilt into a map on load.
+#[derive(Debug, Serialize, Deserialize)]
+struct RouterSnapshot {
+    hub_codes: Vec<String>,
+    trees: Vec<(TreeKey, PrecomputedTree)>,
+}
+
+/// Caches precomputed cheapest-path trees for a configured set of hub
+/// nodes. Not meant to be rebuilt per-request: call `precompute_all`
+/// whenever the backing graph changes meaningfully (initial load, periodic
+/// reload), and `invalidate_edge`/`invalidate_all` on incremental edge/node
+/// updates so a stale tree is never reused.
+pub struct PrecomputedRouter {
+    hub_codes: HashSet<String>,
+    trees: HashMap<TreeKey, PrecomputedTree>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl PrecomputedRouter {
+    pub fn new(hub_codes: impl IntoIterator<Item = String>) -> Self {
+        PrecomputedRouter {
+            hub_codes: hub_codes.into_iter().collect(),
+            trees: HashMap::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn hub_codes(&self) -> &HashSet<String> {
+        &self.hub_codes
+    }
+
+    pub fn hit_count(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn miss_count(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    pub fn tree_count(&self) -> usize {
+        self.trees.len()
+    }
+
+    /// Age of the stalest cached tree, for observability — a large value
+    /// means `precompute_all` hasn't run recently relative to how fast the
+    /// graph is changing.
+    pub fn oldest_tree_age(&self) -> Option<chrono::Duration> {
+        self.trees
+            .values()
+            .map(|t| Utc::now() - t.computed_at)
+            .max()
+    }
+
+    /// (Re)builds every hub x mode-subset x weight-bucket tree from
+    /// scratch. `mode_subsets` and `weight_buckets_kg` are representative
+    /// samples (e.g. "all modes allowed" plus each individual mode, and one
+    /// weight per bucket) — exact inputs outside those samples still work
+    /// via `lookup`'s fallback to a live search, just without the cache.
+    pub fn precompute_all(
+        &mut self,
+        graph: &TransportGraph,
+        mode_subsets: &[HashSet<TransportMode>],
+        weight_buckets_kg: &[f64],
+    ) {
+        self.trees.clear();
+
+        for hub_code in self.hub_codes.clone() {
+            if graph.get_node_index(&hub_code).is_none() {
+                continue;
+            }
+            for modes in mode_subsets {
+                for &weight_kg in weight_buckets_kg {
+                    if let Some(tree) = Self::build_tree(graph, &hub_code, modes, weight_kg) {
+                        let key = TreeKey {
+                            hub_code: hub_code.clone(),
+                            mode_key: mode_key(modes),
+                            weight_bucket: weight_bucket(weight_kg),
+                        };
+                        self.trees.insert(key, tree);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Dijkstra over `calculate_cost(weight_kg)`, skipping inactive and
+    /// sanctioned edges and any edge outside `allowed_modes` (empty means
+    /// unrestricted) — the same filters `Optimizer::search_single_path`
+    /// applies, so a cached path is a drop-in replacement for a live one.
+    fn build_tree(
+        graph: &TransportGraph,
+        hub_code: &str,
+        allowed_modes: &HashSet<TransportMode>,
+        weight_kg: f64,
+    ) -> Option<PrecomputedTree> {
+        let inner_graph = graph.inner();
+        let hub_idx = graph.get_node_index(hub_code)?;
+
+        let mut best_cost: HashMap<String, rust_decimal::Decimal> = HashMap::new();
+        let mut predecessor: HashMap<String, (String, TransportEdge)> = HashMap::new();
+        let mut edge_codes: HashSet<String> = HashSet::new();
+
+        let hub_node = &inner_graph[hub_idx];
+        best_cost.insert(hub_node.code.clone(), rust_decimal::Decimal::ZERO);
+
+        let mut heap = BinaryHeap::new();
+        heap.push(DijkstraState {
+            node_code: hub_node.code.clone(),
+            cost: rust_decimal::Decimal::ZERO,
+        });
+
+        while let Some(DijkstraState { node_code, cost }) = heap.pop() {
+            if best_cost.get(&node_code).is_some_and(|&best| cost > best) {
+                continue; // stale heap entry
+            }
+            let Some(node_idx) = graph.get_node_index(&node_code) else {
+                continue;
+            };
+
+            for edge_ref in inner_graph.edges(node_idx) {
+                let edge = edge_ref.weight();
+                if !edge.active || edge.carrier_sanctioned {
+                    continue;
+                }
+                if !allowed_modes.is_empty() && !allowed_modes.contains(&edge.mode) {
+                    continue;
+                }
+
+                let target = &inner_graph[edge_ref.target()];
+                let new_cost = cost + edge.calculate_cost(weight_kg);
+
+                if best_cost
+                    .get(&target.code)
+                    .is_none_or(|&existing| new_cost < existing)
+                {
+                    best_cost.insert(target.code.clone(), new_cost);
+                    predecessor.insert(target.code.clone(), (node_code.clone(), edge.clone()));
+                    edge_codes.insert(edge.code.clone());
+                    heap.push(DijkstraState {
+                        node_code: target.code.clone(),
+                        cost: new_cost,
+                    });
+                }
+            }
+        }
+
+        Some(PrecomputedTree {
+            predecessor,
+            edge_codes,
+            computed_at: Utc::now(),
+        })
+    }
+
+    /// Looks up the cached cheapest path from `hub_code` to
+    /// `destination_code` for the given mode subset/weight, recording a hit
+    /// or miss. Returns `None` on a miss (no cached tree, or the tree
+    /// didn't reach `destination_code`) — the caller falls back to a live
+    /// search in that case.
+    pub fn lookup(
+        &self,
+        hub_code: &str,
+        allowed_modes: &HashSet<TransportMode>,
+        weight_kg: f64,
+        destination_code: &str,
+    ) -> Option<Vec<(String, TransportEdge)>> {
+        let key = TreeKey {
+            hub_code: hub_code.to_string(),
+            mode_key: mode_key(allowed_modes),
+            weight_bucket: weight_bucket(weight_kg),
+        };
+
+        let path = self.trees.get(&key).and_then(|tree| tree.path_to(destination_code));
+
+        if path.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        path
+    }
+
+    /// Drops every cached tree that routes through `edge_code`, so a
+    /// carrier going sanctioned, an edge going inactive, or a pricing
+    /// change can never be served a stale cached path over it. The
+    /// affected trees are simply dropped, not eagerly recomputed — the
+    /// next `lookup` misses and falls back to a live search, and the next
+    /// `precompute_all` rebuilds them.
+    pub fn invalidate_edge(&mut self, edge_code: &str) {
+        self.trees.retain(|_, tree| !tree.edge_codes.contains(edge_code));
+    }
+
+    /// Drops every cached tree — used when a change is too broad to
+    /// attribute to one edge (e.g. a node disappearing, or a wholesale
+    /// graph swap).
+    pub fn invalidate_all(&mut self) {
+        self.trees.clear();
+    }
+
+    /// Persists the current cache to `path` as JSON, so a warm restart can
+    /// `load` it back instead of recomputing from scratch.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let snapshot = RouterSnapshot {
+            hub_codes: self.hub_codes.iter().cloned().collect(),
+            trees: self
+                .trees
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect(),
+        };
+        let data = serde_json::to_string(&snapshot)?;
+        std::fs::write(path, data)?;
+        Ok(())
+    }
+
+    /// Loads a cache previously written by `save`. Hit/miss counters
+    /// always restart at zero — they describe this process's observed
+    /// traffic, not a property of the cache contents.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        let snapshot: RouterSnapshot = serde_json::from_str(&data)?;
+        Ok(PrecomputedRouter {
+            hub_codes: snapshot.hub_codes.into_iter().collect(),
+            trees: snapshot.trees.into_iter().collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        })
+    }
+
+    /// `load`s the cache at `path` if present and readable, otherwise
+    /// starts empty: a missing or unreadable cache is never fatal.
+    pub fn load_or_new(path: &Path, hub_codes: impl IntoIterator<Item = String>) -> Self {
+        match Self::load(path) {
+            Ok(router) => router,
+            Err(_) => Self::new(hub_codes),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::TransportNode;
+    use rust_decimal::Decimal;
+
+    fn node(code: &str) -> TransportNode {
+        TransportNode {
+            id: format!("id-{code}"),
+            code: code.to_string(),
+            name: code.to_string(),
+            country_code: "XX".to_string(),
+            lat: 0.0,
+            lon: 0.0,
+            modes: vec![TransportMode::Road],
+            avg_dwell_hours: 0.0,
+        }
+    }
+
+    fn edge(code: &str, mode: TransportMode, base_cost_usd: i64) -> TransportEdge {
+        TransportEdge {
+            id: format!("id-{code}"),
+            code: code.to_string(),
+            mode,
+            carrier_code: "CARR".to_string(),
+            carrier_name: "Carrier".to_string(),
+            distance_km: 10.0,
+            base_cost_usd: Decimal::from(base_cost_usd),
+            cost_per_kg: Decimal::ZERO,
+            transit_hours: 1.0,
+            carbon_per_tonne_km: 0.1,
+            carrier_wage_cents: 2000,
+            carrier_safety_rating: 5,
+            carrier_unionized: true,
+            carrier_sanctioned: false,
+            active: true,
+        }
+    }
+
+    fn build_graph() -> TransportGraph {
+        let mut graph = TransportGraph::new();
+        for code in ["A", "B", "C"] {
+            graph.add_node(node(code));
+        }
+        graph.add_edge("A", "B", edge("ab", TransportMode::Road, 10));
+        graph.add_edge("B", "C", edge("bc", TransportMode::Road, 10));
+        graph.add_edge("A", "C", edge("ac", TransportMode::Road, 50));
+        graph
+    }
+
+    #[test]
+    fn test_precompute_all_finds_the_cheapest_path_not_the_direct_edge() {
+        let graph = build_graph();
+        let mut router = PrecomputedRouter::new(["A".to_string()]);
+
+        router.precompute_all(&graph, &[HashSet::new()], &[1000.0]);
+
+        let path = router
+            .lookup("A", &HashSet::new(), 1000.0, "C")
+            .expect("expected a cached path");
+        // $20 via B beats the $50 direct edge.
+        let codes: Vec<&str> = path.iter().map(|(_, e)| e.code.as_str()).collect();
+        assert_eq!(codes, vec!["ab", "bc"]);
+        assert_eq!(router.hit_count(), 1);
+        assert_eq!(router.miss_count(), 0);
+    }
+
+    #[test]
+    fn test_lookup_misses_for_unknown_hub_or_unreached_destination() {
+        let graph = build_graph();
+        let mut router = PrecomputedRouter::new(["A".to_string()]);
+        router.precompute_all(&graph, &[HashSet::new()], &[1000.0]);
+
+        assert!(router.lookup("NOPE", &HashSet::new(), 1000.0, "C").is_none());
+        assert_eq!(router.miss_count(), 1);
+    }
+
+    #[test]
+    fn test_invalidate_edge_drops_only_affected_trees() {
+        let graph = build_graph();
+        let mut router = PrecomputedRouter::new(["A".to_string(), "B".to_string()]);
+        router.precompute_all(&graph, &[HashSet::new()], &[1000.0]);
+        assert_eq!(router.tree_count(), 2);
+
+        // "ab" only appears in A's tree, not B's.
+        router.invalidate_edge("ab");
+        assert_eq!(router.tree_count(), 1);
+        assert!(router.lookup("A", &HashSet::new(), 1000.0, "C").is_none());
+        assert!(router.lookup("B", &HashSet::new(), 1000.0, "C").is_some());
+    }
+
+    #[test]
+    fn test_save_and_load_round_trips_cached_trees() {
+        let graph = build_graph();
+        let mut router = PrecomputedRouter::new(["A".to_string()]);
+        router.precompute_all(&graph, &[HashSet::new()], &[1000.0]);
+
+        let dir = std::env::temp_dir().join(format!("veds-precomputed-test-{}", std::process::id()));
+        std::fs::write(&dir, "").ok(); // ensure the temp path is writable/clean
+        router.save(&dir).unwrap();
+
+        let reloaded = PrecomputedRouter::load(&dir).unwrap();
+        assert_eq!(reloaded.tree_count(), router.tree_count());
+        assert!(reloaded.lookup("A", &HashSet::new(), 1000.0, "C").is_some());
+
+        std::fs::remove_file(&dir).ok();
+    }
+}
@@ -0,0 +1,8 @@
+//! Multi-Stop Routing
+//!
+//! Extensions on top of the single-lane point-to-point machinery in `graph`
+//! for planning one vehicle/vessel through several pickup and drop-off
+//! stops under a capacity constraint, rather than a single origin/
+//! destination pair.
+
+pub mod vrp;